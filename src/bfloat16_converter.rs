@@ -0,0 +1,22 @@
+use crate::common_converter::{parse_hex, Value, ValueCodec};
+use crate::convert_error::{ConvertError, ErrorKind};
+use crate::float_format::FloatFormat;
+use crate::rounding::Rounder;
+
+/// bfloat16: the upper half of an IEEE-754 binary32 word.
+pub struct BFloat16Codec {
+    pub rounder: Rounder,
+}
+
+impl ValueCodec for BFloat16Codec {
+    fn decode(&self, string: &str) -> Result<Value, ConvertError> {
+        let bits = parse_hex(string, 4)? as u32;
+        Ok(Value::Real(f32::from_bits(bits << 16) as f64))
+    }
+
+    fn encode(&self, value: Value) -> Result<String, ErrorKind> {
+        let value = FloatFormat::BFLOAT16.round_value(&value, &self.rounder);
+        let bits = FloatFormat::BFLOAT16.encode(value, &self.rounder);
+        Ok(format!("0x{:04X}", bits))
+    }
+}
@@ -0,0 +1,500 @@
+//! Headless batch conversion, e.g.
+//! `bitconv --from fix16 --src-bits 15 --to float in.txt -o out.txt`.
+//!
+//! Reads stdin and writes stdout unless an input file or `-o` is given.
+//! Exits with 0 on success, 1 when some lines failed to convert and 2 on
+//! usage or I/O errors.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::process::ExitCode;
+
+use bit_converter::file_converter::{convert_lines, is_same_file, Progress};
+use bit_converter::byte_order::ByteOrder;
+use bit_converter::complex_converter::{ComplexStyle, MagnitudeUnit, PhaseUnit};
+use bit_converter::complex_pair_converter::{IqOrder, Packing, ScalarFormat};
+use bit_converter::fix_converter::{Alignment, FixFormat, IntEncoding};
+use bit_converter::int_converter::Radix;
+use bit_converter::overflow::OverflowPolicy;
+use bit_converter::rounding::RoundingMode;
+use bit_converter::value_converter_factory::{ConvertOptions, ConverterFactory, ValueType};
+use bit_converter::value_converter_factory::ValueType::{
+    Complex, ComplexPair, CustomFloat, Fix, Fp8E4M3, Fp8E5M2, Int, Quantized,
+};
+
+const USAGE: &str = "\
+usage: bitconv --from TYPE --to TYPE [options] [INPUT] [-o OUTPUT]
+
+types: float, float64, float32, float16, bfloat16, fp8_e4m3, fp8_e5m2, tf32,
+       int8, int16, int32, int64, uint8, uint16, uint32, uint64,
+       qint8, qint16, qint32, quint8, quint16, quint32,
+       fix, fix16, fix32, complex, complex_pair and its presets
+       complex16, complex32 and fix_complex, or a custom float such as
+       e7m16 or e5m10,unsigned,bias=N,nan-only,finite,no-subnormals
+       whose values all fit in binary64
+
+options:
+  --src-bits N, --dst-bits N      fraction bits of a fixed-point type
+                                  (-64..128)
+  --src-width N, --dst-width N    word width of a fixed-point type or of
+                                  a fix_complex component (2..64)
+  --src-unsigned, --dst-unsigned  fixed-point type is unsigned
+  --src-significant N, --dst-significant N
+                                  bits of a fixed-point or integer word
+                                  that hold the sample (default: all)
+  --src-align A, --dst-align A    whether the sample sits in the lsb
+                                  (default, sign-extended) or msb bits
+  --src-encoding E, --dst-encoding E
+                                  signed fixed-point or integer encoding:
+                                  twos (default), offset (offset binary),
+                                  sign-magnitude or ones
+  --src-order O, --dst-order O    complex pair component order: iq
+                                  (default, real first) or qi
+  --src-component T, --dst-component T
+                                  complex_pair component type, any hex
+                                  scalar type above (default float16);
+                                  the fixed-point, radix-free integer and
+                                  --saturate options apply to it
+  --src-packing P, --dst-packing P
+                                  complex_pair components in one word
+                                  (default) or two words
+  --src-radix R, --dst-radix R    integers as hex (default), dec or bin
+  --src-scale S, --dst-scale S    quantized types stand for
+  --src-zero-point Z, --dst-zero-point Z
+                                  S * (q - Z); default S = 1, Z = 0
+  --src-endian E, --dst-endian E  byte order of hex words: big (default),
+                                  little or halfword (16-bit halves
+                                  swapped)
+  --complex-style STYLE           complex output as algebraic (a + bi,
+                                  default), algebraic-j (a + bj), pair
+                                  ((a, b)), columns (a b), polar (r∠θ,
+                                  radians) or polar-deg (r@deg); input
+                                  accepts all of these. Polar columns
+                                  join mag or db (power) with phase or
+                                  phase-deg, e.g. db+phase-deg
+  --saturate                      FP8 or custom float output saturates
+                                  instead of overflowing to Inf/NaN
+  --rounding MODE                 nearest-even (default), nearest-away,
+                                  toward-zero, floor, ceil, stochastic
+  --seed N                        seed for stochastic rounding
+  --overflow POLICY               saturate (default), wrap, error
+  -o, --output FILE               write to FILE instead of stdout
+  -h, --help                      show this message
+
+Complex input converts to a scalar type only when its imaginary part is
+zero; other samples fail as not representable.
+
+INPUT defaults to stdin; `-` also means stdin.";
+
+#[derive(Default)]
+struct Side {
+    value_type: Option<ValueType>,
+    bits: Option<i32>,
+    width: Option<u32>,
+    unsigned: bool,
+    saturate: bool,
+    radix: Option<Radix>,
+    order: Option<IqOrder>,
+    style: Option<ComplexStyle>,
+    /// The component type as given, so errors name what the user typed.
+    component: Option<String>,
+    packing: Option<Packing>,
+    significant: Option<u32>,
+    alignment: Option<Alignment>,
+    encoding: Option<IntEncoding>,
+    scale: Option<f64>,
+    zero_point: Option<i64>,
+}
+
+impl Side {
+    fn resolve(&self, name: &str) -> Result<ValueType, String> {
+        let value_type = self
+            .value_type
+            .ok_or_else(|| format!("missing --{}", name))?;
+        self.resolve_type(value_type)
+    }
+
+    fn resolve_type(&self, value_type: ValueType) -> Result<ValueType, String> {
+        Ok(match value_type {
+            Fix(format) => Fix(self.fix_format(format)),
+            Fp8E4M3 { .. } => Fp8E4M3 {
+                saturate: self.saturate,
+            },
+            Fp8E5M2 { .. } => Fp8E5M2 {
+                saturate: self.saturate,
+            },
+            CustomFloat { format, .. } => CustomFloat {
+                format,
+                saturate: self.saturate,
+            },
+            Complex { style } => Complex {
+                style: self.style.unwrap_or(style),
+            },
+            ComplexPair {
+                component,
+                order,
+                packing,
+            } => {
+                let component = match (self.component.as_deref(), component) {
+                    (Some(name), _) => self.resolve_component(name)?,
+                    // the fixed-point options also shape a preset's component
+                    (None, ScalarFormat::Fix(format)) => {
+                        ScalarFormat::Fix(self.fix_format(format))
+                    }
+                    (None, component) => component,
+                };
+                ComplexPair {
+                    component,
+                    order: self.order.unwrap_or(order),
+                    packing: self.packing.unwrap_or(packing),
+                }
+            }
+            Int { format, radix } => Int {
+                format: self.sample_layout(format),
+                radix: self.radix.unwrap_or(radix),
+            },
+            Quantized {
+                storage,
+                radix,
+                scale,
+                zero_point,
+            } => Quantized {
+                storage: self.sample_layout(storage),
+                radix: self.radix.unwrap_or(radix),
+                scale: self.scale.unwrap_or(scale),
+                zero_point: self.zero_point.unwrap_or(zero_point),
+            },
+            other => other,
+        })
+    }
+
+    fn resolve_component(&self, name: &str) -> Result<ScalarFormat, String> {
+        let value_type = parse_type(name)?;
+        let error = || format!("{} cannot be a complex component", name);
+        // checked first, as resolving a complex type would recurse
+        value_type.scalar_format().ok_or_else(error)?;
+        self.resolve_type(value_type)?.scalar_format().ok_or_else(error)
+    }
+
+    fn fix_format(&self, mut format: FixFormat) -> FixFormat {
+        if let Some(width) = self.width {
+            format.width = width;
+            format.significant_bits = width;
+        }
+        if let Some(bits) = self.bits {
+            format.frac = bits;
+        }
+        format.signed &= !self.unsigned;
+        self.sample_layout(format)
+    }
+
+    fn sample_layout(&self, mut format: FixFormat) -> FixFormat {
+        if let Some(significant) = self.significant {
+            format.significant_bits = significant;
+        }
+        if let Some(alignment) = self.alignment {
+            format.alignment = alignment;
+        }
+        if let Some(encoding) = self.encoding {
+            format.encoding = encoding;
+        }
+        format
+    }
+}
+
+#[derive(Default)]
+struct Args {
+    src: Side,
+    dst: Side,
+    rounding: Option<String>,
+    seed: u64,
+    overflow: OverflowPolicy,
+    src_byte_order: ByteOrder,
+    dst_byte_order: ByteOrder,
+    input: Option<String>,
+    output: Option<String>,
+}
+
+fn parse_args() -> Result<Option<Args>, String> {
+    let mut args = Args::default();
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+            _ => (arg.clone(), None),
+        };
+        let mut value = || {
+            inline
+                .clone()
+                .or_else(|| iter.next())
+                .ok_or_else(|| format!("{} expects a value", flag))
+        };
+        match flag.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--from" => args.src.value_type = Some(parse_type(&value()?)?),
+            "--to" => args.dst.value_type = Some(parse_type(&value()?)?),
+            "--src-bits" => args.src.bits = Some(parse_frac(&flag, &value()?)?),
+            "--dst-bits" => args.dst.bits = Some(parse_frac(&flag, &value()?)?),
+            "--src-width" => args.src.width = Some(parse_width(&flag, &value()?)?),
+            "--dst-width" => args.dst.width = Some(parse_width(&flag, &value()?)?),
+            "--src-unsigned" => args.src.unsigned = true,
+            "--dst-unsigned" => args.dst.unsigned = true,
+            "--src-significant" => {
+                args.src.significant = Some(parse_significant(&flag, &value()?)?)
+            }
+            "--dst-significant" => {
+                args.dst.significant = Some(parse_significant(&flag, &value()?)?)
+            }
+            "--src-align" => args.src.alignment = Some(parse_alignment(&value()?)?),
+            "--dst-align" => args.dst.alignment = Some(parse_alignment(&value()?)?),
+            "--src-encoding" => args.src.encoding = Some(parse_encoding(&value()?)?),
+            "--dst-encoding" => args.dst.encoding = Some(parse_encoding(&value()?)?),
+            "--src-scale" => args.src.scale = Some(parse_scale(&flag, &value()?)?),
+            "--dst-scale" => args.dst.scale = Some(parse_scale(&flag, &value()?)?),
+            "--src-zero-point" => args.src.zero_point = Some(parse_number(&flag, &value()?)?),
+            "--dst-zero-point" => args.dst.zero_point = Some(parse_number(&flag, &value()?)?),
+            "--src-radix" => args.src.radix = Some(parse_radix(&value()?)?),
+            "--dst-radix" => args.dst.radix = Some(parse_radix(&value()?)?),
+            "--src-order" => args.src.order = Some(parse_order(&value()?)?),
+            "--dst-order" => args.dst.order = Some(parse_order(&value()?)?),
+            "--complex-style" => args.dst.style = Some(parse_style(&value()?)?),
+            "--src-component" => args.src.component = Some(parse_component(&value()?)?),
+            "--dst-component" => args.dst.component = Some(parse_component(&value()?)?),
+            "--src-packing" => args.src.packing = Some(parse_packing(&value()?)?),
+            "--dst-packing" => args.dst.packing = Some(parse_packing(&value()?)?),
+            "--saturate" => args.dst.saturate = true,
+            "--rounding" => args.rounding = Some(value()?),
+            "--seed" => args.seed = parse_number(&flag, &value()?)?,
+            "--overflow" => args.overflow = parse_overflow(&value()?)?,
+            "--src-endian" => args.src_byte_order = parse_byte_order(&value()?)?,
+            "--dst-endian" => args.dst_byte_order = parse_byte_order(&value()?)?,
+            "-o" | "--output" => args.output = Some(value()?),
+            "-" => args.input = Some(arg),
+            _ if arg.starts_with('-') => return Err(format!("unknown option {}", arg)),
+            _ if args.input.is_none() => args.input = Some(arg),
+            _ => return Err(format!("unexpected argument {}", arg)),
+        }
+    }
+    Ok(Some(args))
+}
+
+fn parse_type(string: &str) -> Result<ValueType, String> {
+    ValueType::get_value_type(string).ok_or_else(|| format!("unknown type {}", string))
+}
+
+/// Checks that a component names a type, keeping the name for messages.
+fn parse_component(string: &str) -> Result<String, String> {
+    parse_type(string)?;
+    Ok(string.to_string())
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, string: &str) -> Result<T, String> {
+    string
+        .parse()
+        .map_err(|_| format!("{} expects a number, got {}", flag, string))
+}
+
+fn parse_width(flag: &str, string: &str) -> Result<u32, String> {
+    let width = parse_number(flag, string)?;
+    if !(2..=64).contains(&width) {
+        return Err(format!("{} must be between 2 and 64", flag));
+    }
+    Ok(width)
+}
+
+fn parse_frac(flag: &str, string: &str) -> Result<i32, String> {
+    let frac = parse_number(flag, string)?;
+    if !(FixFormat::MIN_FRAC..=FixFormat::MAX_FRAC).contains(&frac) {
+        return Err(format!(
+            "{} must be between {} and {}",
+            flag,
+            FixFormat::MIN_FRAC,
+            FixFormat::MAX_FRAC
+        ));
+    }
+    Ok(frac)
+}
+
+fn parse_significant(flag: &str, string: &str) -> Result<u32, String> {
+    let significant = parse_number(flag, string)?;
+    if !(1..=64).contains(&significant) {
+        return Err(format!("{} must be between 1 and 64", flag));
+    }
+    Ok(significant)
+}
+
+fn parse_alignment(string: &str) -> Result<Alignment, String> {
+    match string {
+        "lsb" => Ok(Alignment::Lsb),
+        "msb" => Ok(Alignment::Msb),
+        _ => Err(format!("unknown alignment {}", string)),
+    }
+}
+
+fn parse_encoding(string: &str) -> Result<IntEncoding, String> {
+    match string {
+        "twos" => Ok(IntEncoding::TwosComplement),
+        "offset" => Ok(IntEncoding::OffsetBinary),
+        "sign-magnitude" => Ok(IntEncoding::SignMagnitude),
+        "ones" => Ok(IntEncoding::OnesComplement),
+        _ => Err(format!("unknown encoding {}", string)),
+    }
+}
+
+fn parse_scale(flag: &str, string: &str) -> Result<f64, String> {
+    let scale: f64 = parse_number(flag, string)?;
+    if !(scale.is_finite() && scale > 0.0) {
+        return Err(format!("{} must be positive", flag));
+    }
+    Ok(scale)
+}
+
+fn parse_radix(string: &str) -> Result<Radix, String> {
+    match string {
+        "hex" => Ok(Radix::Hex),
+        "dec" => Ok(Radix::Decimal),
+        "bin" => Ok(Radix::Binary),
+        _ => Err(format!("unknown radix {}", string)),
+    }
+}
+
+fn parse_packing(string: &str) -> Result<Packing, String> {
+    match string {
+        "one" => Ok(Packing::OneWord),
+        "two" => Ok(Packing::TwoWords),
+        _ => Err(format!("unknown packing {}", string)),
+    }
+}
+
+fn parse_order(string: &str) -> Result<IqOrder, String> {
+    match string {
+        "iq" => Ok(IqOrder::IHigh),
+        "qi" => Ok(IqOrder::QHigh),
+        _ => Err(format!("unknown I/Q order {}", string)),
+    }
+}
+
+fn parse_style(string: &str) -> Result<ComplexStyle, String> {
+    match string {
+        "algebraic" => Ok(ComplexStyle::Algebraic),
+        "algebraic-j" => Ok(ComplexStyle::AlgebraicJ),
+        "pair" => Ok(ComplexStyle::Pair),
+        "columns" => Ok(ComplexStyle::Columns),
+        "polar" => Ok(ComplexStyle::Polar),
+        "polar-deg" => Ok(ComplexStyle::PolarDegrees),
+        _ => {
+            let mut magnitude = MagnitudeUnit::Off;
+            let mut phase = PhaseUnit::Off;
+            for column in string.split('+') {
+                match column {
+                    "mag" => magnitude = MagnitudeUnit::Linear,
+                    "db" => magnitude = MagnitudeUnit::Decibel,
+                    "phase" => phase = PhaseUnit::Radians,
+                    "phase-deg" => phase = PhaseUnit::Degrees,
+                    _ => return Err(format!("unknown complex style {}", string)),
+                }
+            }
+            Ok(ComplexStyle::PolarColumns { magnitude, phase })
+        }
+    }
+}
+
+fn parse_rounding(string: &str, seed: u64) -> Result<RoundingMode, String> {
+    match string {
+        "nearest-even" => Ok(RoundingMode::NearestEven),
+        "nearest-away" => Ok(RoundingMode::NearestAway),
+        "toward-zero" => Ok(RoundingMode::TowardZero),
+        "floor" => Ok(RoundingMode::Floor),
+        "ceil" => Ok(RoundingMode::Ceil),
+        "stochastic" => Ok(RoundingMode::Stochastic(seed)),
+        _ => Err(format!("unknown rounding mode {}", string)),
+    }
+}
+
+fn parse_byte_order(string: &str) -> Result<ByteOrder, String> {
+    match string {
+        "big" => Ok(ByteOrder::Big),
+        "little" => Ok(ByteOrder::Little),
+        "halfword" => Ok(ByteOrder::HalfwordSwapped),
+        _ => Err(format!("unknown byte order {}", string)),
+    }
+}
+
+fn parse_overflow(string: &str) -> Result<OverflowPolicy, String> {
+    match string {
+        "saturate" => Ok(OverflowPolicy::Saturate),
+        "wrap" => Ok(OverflowPolicy::Wrap),
+        "error" => Ok(OverflowPolicy::Error),
+        _ => Err(format!("unknown overflow policy {}", string)),
+    }
+}
+
+fn run(args: Args) -> Result<ExitCode, String> {
+    let src = args.src.resolve("from")?;
+    let dst = args.dst.resolve("to")?;
+    let rounding = match &args.rounding {
+        Some(rounding) => parse_rounding(rounding, args.seed)?,
+        None => RoundingMode::default(),
+    };
+    let options = ConvertOptions {
+        rounding,
+        overflow: args.overflow,
+        src_byte_order: args.src_byte_order,
+        dst_byte_order: args.dst_byte_order,
+    };
+    let converter = ConverterFactory::create(&src, &dst, &options);
+
+    let input_name = args.input.as_deref().unwrap_or("-");
+    if let Some(output) = &args.output {
+        if input_name != "-" && is_same_file(input_name, output) {
+            return Err(format!("{}: output file is the input file", output));
+        }
+    }
+    let reader: Box<dyn BufRead> = match input_name {
+        "-" => Box::new(io::stdin().lock()),
+        path => Box::new(BufReader::new(
+            File::open(path).map_err(|e| format!("{}: {}", path, e))?,
+        )),
+    };
+    let writer: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(
+            File::create(path).map_err(|e| format!("{}: {}", path, e))?,
+        )),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+
+    let errors = convert_lines(reader, writer, converter.as_ref(), &Progress::default())
+        .map_err(|e| e.to_string())?;
+    for e in &errors {
+        eprintln!("{}: {}", input_name, e);
+    }
+    if converter.overflow_count() > 0 {
+        eprintln!("bitconv: {} samples overflowed", converter.overflow_count());
+    }
+    Ok(if errors.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(1)
+    })
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(message) => {
+            eprintln!("bitconv: {}\n\n{}", message, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match run(args) {
+        Ok(code) => code,
+        Err(message) => {
+            eprintln!("bitconv: {}", message);
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use std::fmt::{Display, Formatter};
+
+use crate::common_converter::{Value, ValueCodec};
+use crate::convert_error::{ConvertError, ErrorKind};
+
+/// Byte order of hex words relative to how they are read and written
+/// natively, most significant digit first.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ByteOrder {
+    #[default]
+    Big,
+    /// Bytes reversed, as in dumps of little-endian memory.
+    Little,
+    /// 16-bit halfwords reversed with their bytes kept in order, e.g.
+    /// `0x3F800000` stored as `0x00003F80`. Words of an odd number of
+    /// bytes are left unchanged.
+    HalfwordSwapped,
+}
+
+impl ByteOrder {
+    pub const ALL: [ByteOrder; 3] = [ByteOrder::Big, ByteOrder::Little, ByteOrder::HalfwordSwapped];
+
+    /// Reorders the bytes of every native-order hex word of `width` bits
+    /// in `string`, keeping prefixes and whitespace. Words are first padded
+    /// to whole bytes, so a 19-bit word is swapped as three bytes. Tokens
+    /// that are not hex words pass through for the codec to report.
+    pub fn from_native(&self, string: &str, width: u32) -> String {
+        self.swap_words(string, width, false)
+    }
+
+    /// The inverse of `from_native`, dropping the zero padding again so that
+    /// the word has at most the digits of a `width`-bit value.
+    pub fn to_native(&self, string: &str, width: u32) -> String {
+        self.swap_words(string, width, true)
+    }
+
+    fn swap_words(&self, string: &str, width: u32, trim: bool) -> String {
+        if *self == ByteOrder::Big {
+            return string.to_string();
+        }
+        let mut swapped = String::with_capacity(string.len());
+        let mut rest = string;
+        while !rest.is_empty() {
+            let word_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            swapped.push_str(&self.swap_word(&rest[..word_end], width, trim));
+            let rest_start = rest[word_end..]
+                .find(|c: char| !c.is_whitespace())
+                .map_or(rest.len(), |i| word_end + i);
+            swapped.push_str(&rest[word_end..rest_start]);
+            rest = &rest[rest_start..];
+        }
+        swapped
+    }
+
+    fn swap_word(&self, word: &str, width: u32, trim: bool) -> String {
+        let (prefix, digits) = match word.strip_prefix("0x").or_else(|| word.strip_prefix("0X")) {
+            Some(digits) => (&word[..2], digits),
+            None => ("", word),
+        };
+        let byte_digits = width.div_ceil(8) as usize * 2;
+        let valid = digits.chars().all(|c| c.is_ascii_hexdigit());
+        if digits.is_empty() || digits.len() > byte_digits || !valid {
+            return word.to_string();
+        }
+        let padded = format!("{:0>width$}", digits, width = byte_digits);
+        let bytes: Vec<&str> = (0..padded.len()).step_by(2).map(|i| &padded[i..i + 2]).collect();
+        let reordered: Vec<&str> = match self {
+            ByteOrder::Big => bytes,
+            ByteOrder::Little => bytes.into_iter().rev().collect(),
+            ByteOrder::HalfwordSwapped if bytes.len().is_multiple_of(2) => {
+                bytes.chunks(2).rev().flatten().copied().collect()
+            }
+            ByteOrder::HalfwordSwapped => bytes,
+        };
+        let reordered = reordered.concat();
+        // padding digits are zero in a valid word; otherwise the codec reports them
+        let padding = byte_digits - width.div_ceil(4) as usize;
+        let reordered = match reordered.split_at(padding) {
+            (zeros, native) if trim && zeros.bytes().all(|b| b == b'0') => native,
+            _ => &reordered,
+        };
+        format!("{}{}", prefix, reordered)
+    }
+}
+
+impl Display for ByteOrder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ByteOrder::Big => write!(f, "big endian"),
+            ByteOrder::Little => write!(f, "little endian"),
+            ByteOrder::HalfwordSwapped => write!(f, "halfword swapped"),
+        }
+    }
+}
+
+/// Wraps the codec of a hex type whose words are `width` bits wide,
+/// swapping words into native order before decoding and back after
+/// encoding.
+pub struct ByteOrderCodec {
+    pub inner: Box<dyn ValueCodec>,
+    pub order: ByteOrder,
+    pub width: u32,
+}
+
+impl ValueCodec for ByteOrderCodec {
+    fn decode(&self, string: &str) -> Result<Value, ConvertError> {
+        self.inner.decode(&self.order.to_native(string, self.width))
+    }
+
+    fn encode(&self, value: Value) -> Result<String, ErrorKind> {
+        Ok(self.order.from_native(&self.inner.encode(value)?, self.width))
+    }
+
+    fn is_complex(&self) -> bool {
+        self.inner.is_complex()
+    }
+
+    fn overflow_count(&self) -> usize {
+        self.inner.overflow_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pads_words_to_whole_bytes() {
+        assert_eq!(ByteOrder::Little.from_native("0x1FC00", 19), "0x00FC01");
+        assert_eq!(ByteOrder::Little.to_native("0x00FC01", 19), "0x1FC00");
+        assert_eq!(ByteOrder::Little.to_native("3F80", 32), "803F0000");
+        assert_eq!(ByteOrder::Little.from_native("0x100", 12), "0x0001");
+        assert_eq!(ByteOrder::Little.to_native("0x0001", 12), "0x100");
+        assert_eq!(ByteOrder::HalfwordSwapped.from_native("0x3F800000", 32), "0x00003F80");
+    }
+
+    #[test]
+    fn keeps_layout_and_foreign_tokens() {
+        let order = ByteOrder::Little;
+        assert_eq!(order.from_native(" 0x0102  0x0304\n", 16), " 0x0201  0x0403\n");
+        assert_eq!(order.to_native("0x1234567", 16), "0x1234567");
+        assert_eq!(order.to_native("12G4", 16), "12G4");
+        // nonzero padding is left for the codec to reject
+        assert_eq!(order.to_native("0x00FC11", 19), "0x11FC00");
+    }
+}
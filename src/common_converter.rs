@@ -1,28 +1,226 @@
-use crate::value_converter_factory::ValueType;
-use crate::value_converter_factory::ValueType::{Complex, Float};
+use std::str::FromStr;
 
-pub trait ValueConverter {
-    fn convert(&self, string: &str) -> String;
+use num_bigint::BigInt;
+use num_traits::FromPrimitive;
 
-    fn is_self_converter(&self) -> bool {
+use crate::convert_error::{ConvertError, ErrorKind};
+use crate::exact::{round_fixed, Decimal};
+use crate::rounding::Rounder;
+
+/// Canonical intermediate value every value type decodes to and encodes from.
+/// Fixed-point samples and decimal text keep their exact value, so that
+/// conversions between them round once from the true value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Real(f64),
+    /// Real and imaginary parts, each a scalar value.
+    Complex(Box<Value>, Box<Value>),
+    /// The binary fraction `raw * 2^-frac`.
+    Fixed { raw: i128, frac: i32 },
+    Decimal(Decimal),
+}
+
+impl Value {
+    pub fn complex(real: Value, imag: Value) -> Value {
+        Value::Complex(Box::new(real), Box::new(imag))
+    }
+
+    pub fn real(&self) -> f64 {
+        match *self {
+            Value::Real(value) => value,
+            Value::Complex(ref real, _) => real.real(),
+            Value::Fixed { raw, frac } => raw as f64 * 2f64.powi(-frac),
+            Value::Decimal(ref decimal) => decimal.approx(),
+        }
+    }
+
+    pub fn imag(&self) -> f64 {
+        match self {
+            Value::Complex(_, imag) => imag.real(),
+            _ => 0.0,
+        }
+    }
+
+    /// The real part, keeping an exact component exact.
+    pub fn real_part(&self) -> Value {
+        match self {
+            Value::Complex(real, _) => (**real).clone(),
+            value => value.clone(),
+        }
+    }
+
+    /// The imaginary part, keeping an exact component exact.
+    pub fn imag_part(&self) -> Value {
+        match self {
+            Value::Complex(_, imag) => (**imag).clone(),
+            _ => Value::Real(0.0),
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Value::Real(value) => *value == 0.0,
+            Value::Complex(real, imag) => real.is_zero() && imag.is_zero(),
+            Value::Fixed { raw, .. } => *raw == 0,
+            Value::Decimal(decimal) => decimal.is_zero(),
+        }
+    }
+
+    /// The value as a scalar, or `Unrepresentable` when it has a nonzero
+    /// imaginary part that a scalar type would drop.
+    pub fn into_scalar(self) -> Result<Value, ErrorKind> {
+        match self {
+            Value::Complex(real, imag) if imag.is_zero() => Ok(*real),
+            Value::Complex(..) => Err(ErrorKind::Unrepresentable),
+            value => Ok(value),
+        }
+    }
+
+    /// Whether the real part is infinite. Fixed-point and decimal values are
+    /// always finite, however far their binary64 approximation overflows.
+    pub fn is_infinite(&self) -> bool {
+        match self {
+            Value::Real(value) => value.is_infinite(),
+            Value::Complex(real, _) => real.is_infinite(),
+            _ => false,
+        }
+    }
+
+    /// Rounds `self * 2^frac` to an integer, exactly for fixed-point and
+    /// decimal values, or returns `None` for NaN.
+    pub fn round_scaled(&self, frac: i32, rounder: &Rounder) -> Option<BigInt> {
+        match self {
+            Value::Fixed { raw, frac: from } => {
+                return Some(round_fixed(*raw, frac as i64 - *from as i64, rounder))
+            }
+            Value::Decimal(decimal) => return Some(decimal.round_scaled(frac, rounder)),
+            Value::Complex(real, _) => return real.round_scaled(frac, rounder),
+            _ => {}
+        }
+        let value = self.real();
+        if value.is_nan() {
+            return None;
+        }
+        let rounded = rounder.round(value * 2f64.powi(frac));
+        // infinities saturate, leaving them to the overflow policy
+        Some(BigInt::from_f64(rounded).unwrap_or_else(|| BigInt::from(rounded as i128)))
+    }
+}
+
+pub trait ValueConverter: Send + Sync {
+    fn convert(&self, string: &str) -> Result<String, ConvertError>;
+
+    /// Number of samples so far that did not fit the destination range.
+    fn overflow_count(&self) -> usize;
+}
+
+/// Decodes one value type into the canonical `Value` and encodes it back.
+/// Scalar codecs are only given complex values without an imaginary part,
+/// already reduced to their real part.
+pub trait ValueCodec: Send + Sync {
+    fn decode(&self, string: &str) -> Result<Value, ConvertError>;
+
+    /// Encoding errors carry no position; the caller points them at the input.
+    fn encode(&self, value: Value) -> Result<String, ErrorKind>;
+
+    /// Whether `encode` writes both parts of a complex value.
+    fn is_complex(&self) -> bool {
         false
     }
+
+    fn overflow_count(&self) -> usize {
+        0
+    }
 }
 
-pub struct SelfConverter {
-    pub(crate) value_type: i32
+/// A scalar type stored as a word of `width` bits, so composite types can
+/// pack several of them.
+pub trait WordCodec: Send + Sync {
+    fn width(&self) -> u32;
+
+    /// The value of the low `width` bits of `bits`.
+    fn decode_bits(&self, bits: u64) -> Value;
+
+    /// Rounds the real part of `value` to the type and returns its bit
+    /// pattern.
+    fn encode_bits(&self, value: &Value) -> Result<u64, ErrorKind>;
+
+    fn overflow_count(&self) -> usize {
+        0
+    }
 }
 
-impl ValueConverter for SelfConverter {
-    fn convert(&self, string: &str) -> String {
-        if self.value_type == Float as i32 && self.value_type == Complex as i32 {
-            format!("{}", string)
+/// Converts between any two value types by pivoting through `Value`.
+pub struct PivotConverter {
+    pub decoder: Box<dyn ValueCodec>,
+    pub encoder: Box<dyn ValueCodec>,
+}
+
+impl ValueConverter for PivotConverter {
+    fn convert(&self, string: &str) -> Result<String, ConvertError> {
+        let value = self.decoder.decode(string)?;
+        let encoded = if self.encoder.is_complex() {
+            self.encoder.encode(value)
         } else {
-            format!("0x{}", string)
-        }
+            value.into_scalar().and_then(|value| self.encoder.encode(value))
+        };
+        encoded.map_err(|kind| ConvertError::new(kind, token_start(string).1))
     }
 
-    fn is_self_converter(&self) -> bool {
-        true
+    fn overflow_count(&self) -> usize {
+        self.encoder.overflow_count()
     }
-}
\ No newline at end of file
+}
+
+/// 1-based character column of the byte offset `index` in `string`.
+pub fn column(string: &str, index: usize) -> usize {
+    string[..index].chars().count() + 1
+}
+
+/// The input with surrounding whitespace removed, and its starting column.
+pub fn token_start(string: &str) -> (&str, usize) {
+    let trimmed = string.trim_start();
+    (trimmed.trim_end(), column(string, string.len() - trimmed.len()))
+}
+
+/// Parses a hex word of at most `digits` digits, optionally prefixed by `0x`.
+pub fn parse_hex(string: &str, digits: usize) -> Result<u64, ConvertError> {
+    parse_word(string, digits, 16, ["0x", "0X"], ErrorKind::InvalidHexDigit)
+}
+
+/// Parses a binary word of at most `digits` digits, optionally prefixed by `0b`.
+pub fn parse_binary(string: &str, digits: usize) -> Result<u64, ConvertError> {
+    parse_word(string, digits, 2, ["0b", "0B"], ErrorKind::InvalidBinaryDigit)
+}
+
+fn parse_word(
+    string: &str,
+    digits: usize,
+    radix: u32,
+    prefixes: [&str; 2],
+    invalid: fn(char) -> ErrorKind,
+) -> Result<u64, ConvertError> {
+    let trimmed = string.trim();
+    let start = string.len() - string.trim_start().len();
+    let (start, word) = match prefixes.iter().find_map(|prefix| trimmed.strip_prefix(prefix)) {
+        Some(word) => (start + 2, word),
+        None => (start, trimmed),
+    };
+    if let Some((i, c)) = word.char_indices().find(|(_, c)| !c.is_digit(radix)) {
+        return Err(ConvertError::new(invalid(c), column(string, start + i)));
+    }
+    if word.is_empty() || word.len() > digits {
+        let kind = ErrorKind::WrongDigitCount {
+            expected: digits,
+            found: word.len(),
+        };
+        return Err(ConvertError::new(kind, column(string, start)));
+    }
+    Ok(u64::from_str_radix(word, radix).expect("validated digits"))
+}
+
+/// Parses a decimal floating-point number.
+pub fn parse_decimal(string: &str) -> Result<f64, ConvertError> {
+    let (token, column) = token_start(string);
+    f64::from_str(token).map_err(|_| ConvertError::new(ErrorKind::InvalidNumber, column))
+}
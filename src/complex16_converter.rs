@@ -1,9 +0,0 @@
-use crate::common_converter::ValueConverter;
-
-pub struct Complex16ToComplexConverter;
-
-impl ValueConverter for Complex16ToComplexConverter {
-    fn convert(&self, string: &str) -> String {
-        todo!()
-    }
-}
\ No newline at end of file
@@ -1,35 +1,288 @@
-use crate::common_converter::ValueConverter;
+use std::fmt::{Display, Formatter};
 
-pub struct ComplexToFloat32Converter;
+use crate::common_converter::{token_start, Value, ValueCodec};
+use crate::convert_error::{ConvertError, ErrorKind};
 
-impl ValueConverter for ComplexToFloat32Converter {
-    fn convert(&self, string: &str) -> String {
-        todo!()
+/// How complex numbers are written. Input in any style is accepted
+/// regardless of the selected one.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ComplexStyle {
+    /// `1.5 - 2i`
+    #[default]
+    Algebraic,
+    /// `1.5 - 2j`
+    AlgebraicJ,
+    /// `(1.5, -2)`
+    Pair,
+    /// `1.5 -2`, real and imaginary columns
+    Columns,
+    /// `2.5∠-0.927`, magnitude and phase in radians
+    Polar,
+    /// `2.5@-53.13`, magnitude and phase in degrees
+    PolarDegrees,
+    /// Space-separated polar quantities for inspecting captures, e.g.
+    /// `7.96 -53.13` for power in dB and phase in degrees. Output only:
+    /// such lines read back as real and imaginary columns.
+    PolarColumns {
+        magnitude: MagnitudeUnit,
+        phase: PhaseUnit,
+    },
+}
+
+impl ComplexStyle {
+    pub const ALL: [ComplexStyle; 7] = [
+        ComplexStyle::Algebraic,
+        ComplexStyle::AlgebraicJ,
+        ComplexStyle::Pair,
+        ComplexStyle::Columns,
+        ComplexStyle::Polar,
+        ComplexStyle::PolarDegrees,
+        ComplexStyle::PolarColumns {
+            magnitude: MagnitudeUnit::Linear,
+            phase: PhaseUnit::Degrees,
+        },
+    ];
+}
+
+/// The magnitude column of `ComplexStyle::PolarColumns`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MagnitudeUnit {
+    Off,
+    /// `|z|`
+    Linear,
+    /// Power `10 log10 |z|^2`
+    Decibel,
+}
+
+/// The phase column of `ComplexStyle::PolarColumns`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PhaseUnit {
+    Off,
+    Radians,
+    Degrees,
+}
+
+impl Display for MagnitudeUnit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MagnitudeUnit::Off => write!(f, "no magnitude"),
+            MagnitudeUnit::Linear => write!(f, "magnitude"),
+            MagnitudeUnit::Decibel => write!(f, "power dB"),
+        }
     }
 }
 
-pub struct ComplexToFloat16Converter;
+impl Display for PhaseUnit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PhaseUnit::Off => write!(f, "no phase"),
+            PhaseUnit::Radians => write!(f, "phase rad"),
+            PhaseUnit::Degrees => write!(f, "phase deg"),
+        }
+    }
+}
 
-impl ValueConverter for ComplexToFloat16Converter {
-    fn convert(&self, string: &str) -> String {
-        todo!()
+impl Display for ComplexStyle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ComplexStyle::Algebraic => write!(f, "a+bi"),
+            ComplexStyle::AlgebraicJ => write!(f, "a+bj"),
+            ComplexStyle::Pair => write!(f, "(a,b)"),
+            ComplexStyle::Columns => write!(f, "a b"),
+            ComplexStyle::Polar => write!(f, "r∠θ"),
+            ComplexStyle::PolarDegrees => write!(f, "r@deg"),
+            ComplexStyle::PolarColumns { .. } => write!(f, "polar columns"),
+        }
     }
 }
 
-pub struct ComplexToFloatConverter;
+/// Complex numbers written as text, e.g. `1.5 - 2i`.
+pub struct ComplexCodec {
+    pub style: ComplexStyle,
+}
+
+impl ValueCodec for ComplexCodec {
+    fn decode(&self, string: &str) -> Result<Value, ConvertError> {
+        let (real, imag) = Self::parse_complex(string)
+            .ok_or_else(|| ConvertError::new(ErrorKind::InvalidNumber, token_start(string).1))?;
+        Ok(Value::complex(Value::Real(real), Value::Real(imag)))
+    }
+
+    fn encode(&self, value: Value) -> Result<String, ErrorKind> {
+        let real = value.real();
+        let imag = value.imag();
+        let magnitude = real.hypot(imag);
+        let phase = imag.atan2(real);
+        Ok(match self.style {
+            ComplexStyle::Algebraic => Self::algebraic(real, imag, 'i'),
+            ComplexStyle::AlgebraicJ => Self::algebraic(real, imag, 'j'),
+            ComplexStyle::Pair => format!("({}, {})", real, imag),
+            ComplexStyle::Columns => format!("{} {}", real, imag),
+            ComplexStyle::Polar => format!("{}∠{}", magnitude, phase),
+            ComplexStyle::PolarDegrees => format!("{}@{}", magnitude, phase.to_degrees()),
+            ComplexStyle::PolarColumns {
+                magnitude: magnitude_unit,
+                phase: phase_unit,
+            } => {
+                let magnitude = match magnitude_unit {
+                    MagnitudeUnit::Off => None,
+                    MagnitudeUnit::Linear => Some(magnitude),
+                    MagnitudeUnit::Decibel => Some(20.0 * magnitude.log10()),
+                };
+                let phase = match phase_unit {
+                    PhaseUnit::Off => None,
+                    PhaseUnit::Radians => Some(phase),
+                    PhaseUnit::Degrees => Some(phase.to_degrees()),
+                };
+                let columns: Vec<_> = magnitude.into_iter().chain(phase).map(|c| c.to_string()).collect();
+                columns.join(" ")
+            }
+        })
+    }
 
-impl ValueConverter for ComplexToFloatConverter {
-    fn convert(&self, string: &str) -> String {
-        todo!()
+    fn is_complex(&self) -> bool {
+        true
     }
 }
 
-pub struct ComplexToComplex16Converter;
+impl ComplexCodec {
+    fn algebraic(real: f64, imag: f64, unit: char) -> String {
+        if imag.is_sign_negative() && !imag.is_nan() {
+            format!("{} - {}{}", real, -imag, unit)
+        } else {
+            format!("{} + {}{}", real, imag, unit)
+        }
+    }
+
+    /// Parses `a+bi` or `a-bj` (either part may be missing), `(a,b)`, two
+    /// whitespace-separated columns `a b`, `r∠θ` with θ in radians, or in
+    /// degrees when suffixed with `°`, and `r@deg`.
+    pub fn parse_complex(string: &str) -> Option<(f64, f64)> {
+        let trimmed = string.trim();
+        if let Some((magnitude, phase)) = trimmed.split_once('∠') {
+            let magnitude = magnitude.trim().parse().ok()?;
+            let phase = phase.trim();
+            return Some(match phase.strip_suffix('°') {
+                Some(degrees) => Self::from_polar_degrees(magnitude, degrees.trim().parse().ok()?),
+                None => {
+                    let phase: f64 = phase.parse().ok()?;
+                    (magnitude * phase.cos(), magnitude * phase.sin())
+                }
+            });
+        }
+        if let Some((magnitude, degrees)) = trimmed.split_once('@') {
+            let degrees = degrees.trim();
+            let degrees = degrees.strip_suffix('°').unwrap_or(degrees);
+            return Some(Self::from_polar_degrees(
+                magnitude.trim().parse().ok()?,
+                degrees.trim().parse().ok()?,
+            ));
+        }
+        let pair = trimmed
+            .strip_prefix('(')
+            .and_then(|pair| pair.strip_suffix(')'))
+            .unwrap_or(trimmed);
+        if let Some((real, imag)) = pair.split_once(',') {
+            return Some((real.trim().parse().ok()?, imag.trim().parse().ok()?));
+        }
+        if let [real, imag] = trimmed.split_whitespace().collect::<Vec<_>>()[..] {
+            if let (Ok(real), Ok(imag)) = (real.parse(), imag.parse()) {
+                return Some((real, imag));
+            }
+        }
+        Self::parse_algebraic(trimmed)
+    }
+
+    fn parse_algebraic(string: &str) -> Option<(f64, f64)> {
+        // whitespace may only surround the signs, not split a number
+        let tokens: Vec<_> = string.split_whitespace().collect();
+        let signs = ['+', '-'];
+        if tokens.windows(2).any(|pair| !pair[0].ends_with(signs) && !pair[1].starts_with(signs)) {
+            return None;
+        }
+        let string: String = string.chars().filter(|c| !c.is_whitespace()).collect();
+        let Some(body) = string.strip_suffix(['i', 'j']) else {
+            return Some((string.parse().ok()?, 0.0));
+        };
+        // the sign separating the parts is the last one not belonging to an exponent
+        let split = body.char_indices().rev().find(|&(i, c)| {
+            i > 0 && (c == '+' || c == '-') && !body[..i].ends_with(['e', 'E'])
+        });
+        let (real, imag) = match split {
+            Some((i, _)) => (body[..i].parse().ok()?, &body[i..]),
+            None => (0.0, body),
+        };
+        let imag = match imag {
+            "" | "+" => 1.0,
+            "-" => -1.0,
+            imag => imag.parse().ok()?,
+        };
+        Some((real, imag))
+    }
 
-impl ValueConverter for ComplexToComplex16Converter {
-    fn convert(&self, string: &str) -> String {
-        todo!()
+    /// Exact on the axes, where `sin_cos` would leave rounding residue.
+    fn from_polar_degrees(magnitude: f64, degrees: f64) -> (f64, f64) {
+        let (sin, cos) = match degrees.rem_euclid(360.0) {
+            0.0 => (0.0, 1.0),
+            90.0 => (1.0, 0.0),
+            180.0 => (0.0, -1.0),
+            270.0 => (-1.0, 0.0),
+            _ => degrees.to_radians().sin_cos(),
+        };
+        (magnitude * cos, magnitude * sin)
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value_converter_factory::{ConvertOptions, ConverterFactory, ValueType};
 
+    #[test]
+    fn parses_every_notation() {
+        let polar = (2.0 * 0.5f64.cos(), 2.0 * 0.5f64.sin());
+        let polar_degrees = (2.0 * 30f64.to_radians().cos(), 2.0 * 30f64.to_radians().sin());
+        for (input, expected) in [
+            ("1.5+2i", (1.5, 2.0)),
+            ("1.5 - 2j", (1.5, -2.0)),
+            ("-3i", (0.0, -3.0)),
+            ("-i", (0.0, -1.0)),
+            ("+j", (0.0, 1.0)),
+            ("4", (4.0, 0.0)),
+            ("1e-3+2e+4i", (1e-3, 2e4)),
+            ("-1E-3-2E-4j", (-1e-3, -2e-4)),
+            ("(1.5, -2)", (1.5, -2.0)),
+            ("1.5,-2", (1.5, -2.0)),
+            ("1.5 -2", (1.5, -2.0)),
+            ("  1.5\t-2  ", (1.5, -2.0)),
+            ("2∠0.5", polar),
+            ("2 ∠ 30°", polar_degrees),
+            ("2@30", polar_degrees),
+            ("2@90", (0.0, 2.0)),
+            ("2@-180°", (-2.0, 0.0)),
+        ] {
+            assert_eq!(ComplexCodec::parse_complex(input), Some(expected), "{}", input);
+        }
+    }
+
+    #[test]
+    fn keeps_the_imaginary_part_from_scalar_types() {
+        let complex = ValueType::get_value_type("complex").unwrap();
+        let float = ConverterFactory::create(&complex, &ValueType::Float, &ConvertOptions::default());
+        assert_eq!(float.convert("1.5-0i"), Ok("1.5".to_string()));
+        let error = ConvertError::new(ErrorKind::Unrepresentable, 2);
+        assert_eq!(float.convert(" 1+2i"), Err(error));
+        let fix16 = ValueType::get_value_type("fix16").unwrap();
+        let fix16 = ConverterFactory::create(&complex, &fix16, &ConvertOptions::default());
+        assert_eq!(fix16.convert("1e-300i").unwrap_err().kind, ErrorKind::Unrepresentable);
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        for input in [
+            "", "1 2 3", "1 2i", "1 2 + 3i", "1+2k", "(1,)", "(1, 2", "2∠", "@30", "i1", "1++2i",
+        ] {
+            assert_eq!(ComplexCodec::parse_complex(input), None, "{}", input);
+        }
+    }
+}
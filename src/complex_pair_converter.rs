@@ -0,0 +1,223 @@
+use std::fmt::{Display, Formatter};
+
+use crate::common_converter::{parse_hex, token_start, Value, ValueCodec, WordCodec};
+use crate::convert_error::{ConvertError, ErrorKind};
+use crate::fix_converter::FixFormat;
+use crate::float_format::FloatFormat;
+
+/// The bit layout of a scalar component: every hex scalar `ValueType`
+/// is either a float format or a fixed-point format (integers being
+/// fixed point without fraction bits).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScalarFormat {
+    Float { format: FloatFormat, saturate: bool },
+    Fix(FixFormat),
+}
+
+impl ScalarFormat {
+    pub fn width(&self) -> u32 {
+        match self {
+            ScalarFormat::Float { format, .. } => format.width(),
+            ScalarFormat::Fix(format) => format.width,
+        }
+    }
+}
+
+impl Default for ScalarFormat {
+    fn default() -> Self {
+        ScalarFormat::Float {
+            format: FloatFormat::BINARY16,
+            saturate: false,
+        }
+    }
+}
+
+impl Display for ScalarFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScalarFormat::Float { format, .. } => write!(f, "{}", format),
+            ScalarFormat::Fix(format) => write!(f, "{}", format),
+        }
+    }
+}
+
+/// Which component of a packed complex sample comes first: the upper half
+/// of a single word, or the first of two words.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum IqOrder {
+    /// I (real) first, Q (imaginary) second.
+    #[default]
+    IHigh,
+    /// Q (imaginary) first, I (real) second.
+    QHigh,
+}
+
+impl Display for IqOrder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IqOrder::IHigh => write!(f, "I:Q"),
+            IqOrder::QHigh => write!(f, "Q:I"),
+        }
+    }
+}
+
+/// Whether both components share one hex word or are written as two
+/// whitespace-separated words.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Packing {
+    #[default]
+    OneWord,
+    TwoWords,
+}
+
+impl Packing {
+    /// Widest component that still packs into a single 64-bit word.
+    pub const MAX_ONE_WORD_COMPONENT_WIDTH: u32 = 32;
+
+    /// The packing used for components of `width` bits, which is two words
+    /// when they do not fit one.
+    pub fn for_component_width(self, width: u32) -> Packing {
+        if width > Self::MAX_ONE_WORD_COMPONENT_WIDTH {
+            Packing::TwoWords
+        } else {
+            self
+        }
+    }
+}
+
+impl Display for Packing {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Packing::OneWord => write!(f, "one word"),
+            Packing::TwoWords => write!(f, "two words"),
+        }
+    }
+}
+
+/// Complex samples made of two components of any hex scalar type, e.g.
+/// `0x3C00C000` for float16 `1 - 2i` in one word, or `0x0001 0xFFFE` for an
+/// int16 pair in two words. Components wider than 32 bits always take
+/// two words.
+pub struct ComplexPairCodec {
+    pub component: Box<dyn WordCodec>,
+    pub order: IqOrder,
+    pub packing: Packing,
+}
+
+impl ComplexPairCodec {
+    fn packing(&self) -> Packing {
+        self.packing.for_component_width(self.component.width())
+    }
+
+    fn digits(&self, width: u32) -> usize {
+        width.div_ceil(4) as usize
+    }
+
+    /// Parses one word of `width` bits starting at column `offset` of the line.
+    fn parse_word(&self, word: &str, width: u32, offset: usize) -> Result<u64, ConvertError> {
+        let shift_column = |e: ConvertError| ConvertError::new(e.kind, e.column + offset);
+        let bits = parse_hex(word, self.digits(width)).map_err(shift_column)?;
+        if width < 64 && bits >> width != 0 {
+            return Err(ConvertError::new(ErrorKind::OutOfRange, token_start(word).1 + offset));
+        }
+        Ok(bits)
+    }
+}
+
+impl ValueCodec for ComplexPairCodec {
+    fn decode(&self, string: &str) -> Result<Value, ConvertError> {
+        let width = self.component.width();
+        let (first, second) = match self.packing() {
+            Packing::OneWord => {
+                let bits = self.parse_word(string, 2 * width, 0)?;
+                (bits >> width, bits & ((1 << width) - 1))
+            }
+            Packing::TwoWords => {
+                let (token, column) = token_start(string);
+                let Some((first, second)) = token.split_once(char::is_whitespace) else {
+                    return Err(ConvertError::new(ErrorKind::InvalidNumber, column));
+                };
+                let second_offset = column - 1 + token.len() - second.len();
+                (
+                    self.parse_word(first, width, column - 1)?,
+                    self.parse_word(second, width, second_offset)?,
+                )
+            }
+        };
+        let first = self.component.decode_bits(first);
+        let second = self.component.decode_bits(second);
+        Ok(match self.order {
+            IqOrder::IHigh => Value::complex(first, second),
+            IqOrder::QHigh => Value::complex(second, first),
+        })
+    }
+
+    fn encode(&self, value: Value) -> Result<String, ErrorKind> {
+        let real = self.component.encode_bits(&value.real_part())?;
+        let imag = self.component.encode_bits(&value.imag_part())?;
+        let (first, second) = match self.order {
+            IqOrder::IHigh => (real, imag),
+            IqOrder::QHigh => (imag, real),
+        };
+        let width = self.component.width();
+        Ok(match self.packing() {
+            Packing::OneWord => format!(
+                "0x{:0digits$X}",
+                (first << width) | second,
+                digits = self.digits(2 * width)
+            ),
+            Packing::TwoWords => format!(
+                "0x{:0digits$X} 0x{:0digits$X}",
+                first,
+                second,
+                digits = self.digits(width)
+            ),
+        })
+    }
+
+    fn is_complex(&self) -> bool {
+        true
+    }
+
+    fn overflow_count(&self) -> usize {
+        self.component.overflow_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value_converter_factory::{ConvertOptions, ConverterFactory, ValueType};
+
+    fn int64_pair() -> ValueType {
+        let int64 = ValueType::get_value_type("int64").unwrap();
+        ValueType::ComplexPair {
+            component: int64.scalar_format().unwrap(),
+            order: IqOrder::IHigh,
+            packing: Packing::TwoWords,
+        }
+    }
+
+    fn convert(src: &ValueType, dst: &ValueType, input: &str) -> String {
+        ConverterFactory::create(src, dst, &ConvertOptions::default())
+            .convert(input)
+            .unwrap()
+    }
+
+    #[test]
+    fn keeps_wide_integer_components_exact() {
+        let pair = int64_pair();
+        for input in [
+            "0x1234567890ABCDEF 0x0000000000000001",
+            "0x8000000000000000 0x7FFFFFFFFFFFFFFF",
+            "0xFFFFFFFFFFFFFFFF 0x0020000000000001",
+        ] {
+            assert_eq!(convert(&pair, &pair, input), input);
+        }
+        let int64 = ValueType::get_value_type("int64").unwrap();
+        assert_eq!(
+            convert(&int64, &pair, "0x1234567890ABCDEF"),
+            "0x1234567890ABCDEF 0x0000000000000000"
+        );
+    }
+}
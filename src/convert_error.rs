@@ -0,0 +1,67 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ErrorKind {
+    InvalidHexDigit(char),
+    InvalidBinaryDigit(char),
+    WrongDigitCount { expected: usize, found: usize },
+    InvalidNumber,
+    /// The value does not fit the destination and the overflow policy is `Error`,
+    /// or the input word has bits set beyond its type's width.
+    OutOfRange,
+    /// The value has no representation in the destination, e.g. NaN in fixed point.
+    Unrepresentable,
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorKind::InvalidHexDigit(c) => write!(f, "invalid hex digit '{}'", c),
+            ErrorKind::InvalidBinaryDigit(c) => write!(f, "invalid binary digit '{}'", c),
+            ErrorKind::WrongDigitCount { expected, found } => {
+                write!(f, "expected at most {} digits, found {}", expected, found)
+            }
+            ErrorKind::InvalidNumber => write!(f, "invalid number"),
+            ErrorKind::OutOfRange => write!(f, "value out of range"),
+            ErrorKind::Unrepresentable => write!(f, "value not representable"),
+        }
+    }
+}
+
+/// A failed conversion, located by 1-based line (when converting a file)
+/// and 1-based character column within the input.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConvertError {
+    pub kind: ErrorKind,
+    pub line: Option<usize>,
+    pub column: usize,
+}
+
+impl ConvertError {
+    pub fn new(kind: ErrorKind, column: usize) -> Self {
+        Self {
+            kind,
+            line: None,
+            column,
+        }
+    }
+
+    pub fn at_line(self, line: usize) -> Self {
+        Self {
+            line: Some(line),
+            ..self
+        }
+    }
+}
+
+impl Display for ConvertError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}, column {}: {}", line, self.column, self.kind),
+            None => write!(f, "column {}: {}", self.column, self.kind),
+        }
+    }
+}
+
+impl Error for ConvertError {}
@@ -0,0 +1,223 @@
+use num_bigint::{BigInt, BigUint, Sign};
+use num_traits::{ToPrimitive, Zero};
+
+use crate::rounding::Rounder;
+
+/// Powers of ten beyond this are not expanded; such values are far outside
+/// any fixed-point format and go through binary64 instead.
+const MAX_EXACT_EXPONENT: i64 = 4096;
+
+/// A decimal number exactly as written, `digits * 10^exponent`, so that
+/// rounding it to fixed point sees every digit rather than the nearest
+/// binary64.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Decimal {
+    negative: bool,
+    digits: BigUint,
+    exponent: i64,
+    /// The nearest binary64, for destinations that are not exact.
+    approx: f64,
+}
+
+impl Decimal {
+    /// Reads a finite number in Rust's float syntax whose binary64 value is
+    /// `approx`. Returns `None` for infinities, NaN and extreme exponents.
+    pub fn parse(token: &str, approx: f64) -> Option<Decimal> {
+        if !approx.is_finite() {
+            return None;
+        }
+        let (mantissa, exponent) = match token.find(['e', 'E']) {
+            Some(i) => (&token[..i], token[i + 1..].parse::<i64>().ok()?),
+            None => (token, 0),
+        };
+        let (negative, mantissa) = match mantissa.strip_prefix('-') {
+            Some(mantissa) => (true, mantissa),
+            None => (false, mantissa.strip_prefix('+').unwrap_or(mantissa)),
+        };
+        let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let exponent = exponent.checked_sub(fraction.len() as i64)?;
+        if exponent.abs() > MAX_EXACT_EXPONENT {
+            return None;
+        }
+        let digits = BigUint::parse_bytes(format!("{}{}", integer, fraction).as_bytes(), 10)?;
+        Some(Decimal {
+            negative,
+            digits,
+            exponent,
+            approx,
+        })
+    }
+
+    pub fn approx(&self) -> f64 {
+        self.approx
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_zero()
+    }
+
+    /// Rounds `self * 2^frac` to an integer.
+    pub fn round_scaled(&self, frac: i32, rounder: &Rounder) -> BigInt {
+        let mut numerator = self.digits.clone();
+        let mut denominator = BigUint::from(1u32);
+        // parse bounds the exponent, so the power of five stays small
+        let five = BigUint::from(5u32).pow(self.exponent.unsigned_abs() as u32);
+        if self.exponent >= 0 {
+            numerator *= five;
+        } else {
+            denominator *= five;
+        }
+        let two = self.exponent + frac as i64;
+        if two >= 0 {
+            numerator <<= two as u64;
+        } else {
+            denominator <<= two.unsigned_abs();
+        }
+        round_ratio(self.negative, numerator, &denominator, rounder)
+    }
+}
+
+/// Rounds the fixed-point value `raw * 2^shift` to an integer; see
+/// `round_ratio`.
+pub fn round_fixed(raw: i128, shift: i64, rounder: &Rounder) -> BigInt {
+    if shift >= 0 {
+        return BigInt::from(raw) << shift as u64;
+    }
+    let magnitude = BigUint::from(raw.unsigned_abs());
+    // past 64 bits below the magnitude the fraction rounds like any tiny
+    // one, so the denominator need not grow with the shift
+    let shift = shift.unsigned_abs().min(magnitude.bits() + 65);
+    round_ratio(raw < 0, magnitude, &(BigUint::from(1u32) << shift), rounder)
+}
+
+/// Rounds `numerator / denominator` with the sign `negative` to an integer.
+pub fn round_ratio(
+    negative: bool,
+    numerator: BigUint,
+    denominator: &BigUint,
+    rounder: &Rounder,
+) -> BigInt {
+    let truncated = &numerator / denominator;
+    let remainder = numerator % denominator;
+    let rounded = if remainder.is_zero() {
+        truncated
+    } else {
+        let half = (&remainder << 1u32).cmp(denominator);
+        let odd = truncated.bit(0);
+        // the top 64 bits of the fraction are plenty for stochastic rounding
+        let fraction = ((remainder << 64u32) / denominator)
+            .to_u64()
+            .unwrap_or(u64::MAX) as f64
+            / 2f64.powi(64);
+        truncated + rounder.rounds_up(half, odd, fraction, negative) as u32
+    };
+    let sign = if negative { Sign::Minus } else { Sign::Plus };
+    BigInt::from_biguint(sign, rounded)
+}
+
+/// The exact decimal expansion of `raw * 2^-frac`, without trailing zeros.
+pub fn fixed_to_decimal(raw: i128, frac: i32) -> String {
+    if (frac as i64).abs() > MAX_EXACT_EXPONENT {
+        return format!("{}", raw as f64 * 2f64.powi(-frac));
+    }
+    if frac <= 0 {
+        return (BigInt::from(raw) << -frac as u64).to_string();
+    }
+    let frac = frac as usize;
+    // raw / 2^frac == raw * 5^frac / 10^frac
+    let digits = BigUint::from(raw.unsigned_abs()) * BigUint::from(5u32).pow(frac as u32);
+    let digits = digits.to_string();
+    let digits = format!("{:0>width$}", digits, width = frac + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - frac);
+    let fraction = fraction.trim_end_matches('0');
+    let sign = if raw < 0 { "-" } else { "" };
+    if fraction.is_empty() {
+        format!("{}{}", sign, integer)
+    } else {
+        format!("{}{}.{}", sign, integer, fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rounding::RoundingMode;
+
+    fn decimal(token: &str) -> Decimal {
+        Decimal::parse(token, token.parse().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn prints_exact_expansions() {
+        assert_eq!(fixed_to_decimal(1, 31), "0.0000000004656612873077392578125");
+        assert_eq!(fixed_to_decimal(0x7FFF_FFFF, 31), "0.9999999995343387126922607421875");
+        assert_eq!(fixed_to_decimal(-0x8000_0000, 31), "-1");
+        assert_eq!(fixed_to_decimal(-3, 1), "-1.5");
+        assert_eq!(fixed_to_decimal(0, 15), "0");
+        // more fraction bits than the word has
+        assert_eq!(fixed_to_decimal(1, 40), "0.0000000000009094947017729282379150390625");
+        // a negative fraction count scales up
+        assert_eq!(fixed_to_decimal(3, -4), "48");
+        assert_eq!(fixed_to_decimal(-3, -4), "-48");
+        assert_eq!(fixed_to_decimal(u64::MAX as i128, 0), "18446744073709551615");
+    }
+
+    #[test]
+    fn parses_decimals_exactly() {
+        let rounder = Rounder::new(RoundingMode::NearestEven);
+        assert_eq!(decimal("0.1").round_scaled(31, &rounder), BigInt::from(0x0CCC_CCCD));
+        assert_eq!(decimal("-0.1").round_scaled(31, &rounder), BigInt::from(-0x0CCC_CCCD));
+        assert_eq!(decimal("1.5e3").round_scaled(0, &rounder), BigInt::from(1500));
+        assert_eq!(decimal(".25").round_scaled(2, &rounder), BigInt::from(1));
+        assert_eq!(decimal("5.").round_scaled(-1, &rounder), BigInt::from(2));
+        assert_eq!(decimal("+2E-1").round_scaled(-2, &rounder), BigInt::from(0));
+        let max = decimal("0.9999999995343387126922607421875");
+        assert_eq!(max.round_scaled(31, &rounder), BigInt::from(0x7FFF_FFFF));
+        assert_eq!(Decimal::parse("inf", f64::INFINITY), None);
+        assert_eq!(Decimal::parse("1e-5000", 0.0), None);
+    }
+
+    #[test]
+    fn rounds_ties_by_mode() {
+        use RoundingMode::*;
+        // 2.5, 3.5, -2.5 and just above 2.5, at two fraction bits
+        let values = [(10, false), (14, false), (-10, false), (10, true)];
+        for (mode, expected) in [
+            (NearestEven, [2, 4, -2, 3]),
+            (NearestAway, [3, 4, -3, 3]),
+            (TowardZero, [2, 3, -2, 2]),
+            (Floor, [2, 3, -3, 2]),
+            (Ceil, [3, 4, -2, 3]),
+        ] {
+            let rounder = Rounder::new(mode);
+            for ((raw, above), expected) in values.into_iter().zip(expected) {
+                let rounded = if above {
+                    decimal("2.5000000000000000000000000001").round_scaled(0, &rounder)
+                } else {
+                    round_fixed(raw, -2, &rounder)
+                };
+                assert_eq!(rounded, BigInt::from(expected), "{:?} {}", mode, raw);
+            }
+        }
+    }
+
+    #[test]
+    fn shifts_without_rounding_left() {
+        let rounder = Rounder::new(RoundingMode::NearestEven);
+        assert_eq!(round_fixed(-3, 100, &rounder), BigInt::from(-3) << 100u32);
+        assert_eq!(round_fixed(i128::MIN, 0, &rounder), BigInt::from(i128::MIN));
+        assert_eq!(round_fixed(3, 5015, &rounder), BigInt::from(3) << 5015u32);
+    }
+
+    #[test]
+    fn shifts_far_right() {
+        use RoundingMode::*;
+        for (mode, expected) in [(NearestEven, [0, 0]), (Floor, [0, -1]), (Ceil, [1, 0])] {
+            let rounder = Rounder::new(mode);
+            assert_eq!(round_fixed(3, -5000, &rounder), BigInt::from(expected[0]), "{:?}", mode);
+            assert_eq!(round_fixed(-3, -5000, &rounder), BigInt::from(expected[1]), "{:?}", mode);
+        }
+        let rounder = Rounder::new(RoundingMode::NearestEven);
+        assert_eq!(round_fixed(i128::MAX, -i64::MAX, &rounder), BigInt::from(0));
+    }
+}
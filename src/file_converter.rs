@@ -0,0 +1,238 @@
+use std::ffi::OsStr;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+
+use crate::common_converter::ValueConverter;
+use crate::convert_error::ConvertError;
+use crate::value_converter_factory::ValueType;
+
+pub const DEFAULT_NAME_TEMPLATE: &str = "{stem}_out.{ext}";
+
+/// Progress of a running conversion, shared with the thread observing it.
+#[derive(Default)]
+pub struct Progress {
+    lines: AtomicUsize,
+    bytes: AtomicU64,
+    total_bytes: AtomicU64,
+    cancelled: AtomicBool,
+}
+
+impl Progress {
+    pub fn lines(&self) -> usize {
+        self.lines.load(Ordering::Relaxed)
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    /// Size of the input, or zero when unknown.
+    pub fn total_bytes(&self) -> u64 {
+        self.total_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Fraction of the input processed so far, if its size is known.
+    pub fn fraction(&self) -> Option<f32> {
+        match self.total_bytes() {
+            0 => None,
+            total => Some((self.bytes() as f64 / total as f64).min(1.0) as f32),
+        }
+    }
+
+    /// Asks the conversion to stop after the current line.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Converts `src` line by line into `dst`, creating missing parent
+/// directories. An existing `dst` is only replaced when `overwrite` is set,
+/// and never when it is `src` itself. Lines that fail to convert are left
+/// empty in the output and returned as errors. A cancelled conversion
+/// removes the partial output and fails with `io::ErrorKind::Interrupted`.
+pub fn process_file(
+    src: &str,
+    dst: &str,
+    overwrite: bool,
+    converter: &dyn ValueConverter,
+    progress: &Progress,
+) -> io::Result<Vec<ConvertError>> {
+    let file_in = File::open(src)?;
+    progress
+        .total_bytes
+        .store(file_in.metadata()?.len(), Ordering::Relaxed);
+    let dst = Path::new(dst);
+    // creating the output would truncate the input before it is read
+    if is_same_file(src, dst) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "output file is the input file"));
+    }
+    if let Some(parent) = dst.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file_out = if overwrite {
+        File::create(dst)?
+    } else {
+        File::create_new(dst)?
+    };
+    let result = convert_lines(BufReader::new(file_in), BufWriter::new(file_out), converter, progress);
+    // a cancel arriving after the last line leaves a complete output
+    if result.as_ref().is_err_and(|e| e.kind() == io::ErrorKind::Interrupted) {
+        fs::remove_file(dst)?;
+    }
+    result
+}
+
+/// Whether both paths name one existing file.
+pub fn is_same_file(a: impl AsRef<Path>, b: impl AsRef<Path>) -> bool {
+    fs::canonicalize(a).is_ok_and(|a| fs::canonicalize(b).is_ok_and(|b| a == b))
+}
+
+/// Derives an output path next to `src` from `template`, which may use
+/// `{stem}`, `{ext}`, `{src_type}` and `{dst_type}`. A `.{ext}` suffix is
+/// dropped when `src` has no extension.
+pub fn output_path(template: &str, src: &str, src_type: &ValueType, dst_type: &ValueType) -> String {
+    let src = Path::new(src);
+    let stem = src.file_stem().and_then(OsStr::to_str).unwrap_or("");
+    let ext = src.extension().and_then(OsStr::to_str).unwrap_or("");
+    let template = if ext.is_empty() {
+        template.replace(".{ext}", "")
+    } else {
+        template.to_string()
+    };
+    let file_name = template
+        .replace("{stem}", stem)
+        .replace("{ext}", ext)
+        .replace("{src_type}", &src_type.to_string())
+        .replace("{dst_type}", &dst_type.to_string());
+    src.with_file_name(file_name).to_string_lossy().into_owned()
+}
+
+/// Converts every line of `reader` into `writer`, keeping blank and failed
+/// lines empty so the output stays aligned with the input.
+pub fn convert_lines<R: BufRead, W: Write>(
+    mut reader: R,
+    mut writer: W,
+    converter: &dyn ValueConverter,
+    progress: &Progress,
+) -> io::Result<Vec<ConvertError>> {
+    let mut errors = Vec::new();
+    let mut line = String::new();
+    for index in 0.. {
+        line.clear();
+        let read = reader.read_line(&mut line)?;
+        if read == 0 {
+            break;
+        }
+        // checked after reaching the end, so a late cancel keeps the output
+        if progress.is_cancelled() {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "conversion cancelled"));
+        }
+        progress.lines.fetch_add(1, Ordering::Relaxed);
+        progress.bytes.fetch_add(read as u64, Ordering::Relaxed);
+        let line = line.trim_end_matches(['\n', '\r']);
+        if !line.trim().is_empty() {
+            match converter.convert(line) {
+                Ok(value) => writer.write_all(value.as_bytes())?,
+                Err(e) => errors.push(e.at_line(index + 1)),
+            }
+        }
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::value_converter_factory::{ConvertOptions, ConverterFactory};
+
+    /// A temporary directory holding `in.txt`, removed when dropped so that
+    /// a failing test does not leave it behind.
+    struct Scratch(PathBuf);
+
+    impl Scratch {
+        fn new(name: &str, input: &str) -> Scratch {
+            let dir = std::env::temp_dir().join(format!("bitconv-{}-{}", name, std::process::id()));
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("in.txt"), input).unwrap();
+            Scratch(dir)
+        }
+
+        fn path(&self, name: &str) -> String {
+            self.0.join(name).to_string_lossy().into_owned()
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn float_to_float16() -> Box<dyn ValueConverter> {
+        let options = ConvertOptions::default();
+        ConverterFactory::create(&ValueType::Float, &ValueType::Float16, &options)
+    }
+
+    #[test]
+    fn refuses_to_overwrite_the_input() {
+        let scratch = Scratch::new("same", "1.5\n");
+        let (src, dst) = (scratch.path("in.txt"), scratch.path("./in.txt"));
+        let converter = float_to_float16();
+        let result = process_file(&src, &dst, true, converter.as_ref(), &Progress::default());
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(fs::read_to_string(src).unwrap(), "1.5\n");
+    }
+
+    #[test]
+    fn cancelling_removes_the_partial_output() {
+        let scratch = Scratch::new("cancel", "1.5\n");
+        let (src, dst) = (scratch.path("in.txt"), scratch.path("out.txt"));
+        let progress = Progress::default();
+        progress.cancel();
+        let result = process_file(&src, &dst, false, float_to_float16().as_ref(), &progress);
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::Interrupted);
+        assert!(!Path::new(&dst).exists());
+    }
+
+    /// Cancels the conversion while converting the given line.
+    struct CancelOn<'a> {
+        line: &'a str,
+        progress: &'a Progress,
+    }
+
+    impl ValueConverter for CancelOn<'_> {
+        fn convert(&self, string: &str) -> Result<String, ConvertError> {
+            if string == self.line {
+                self.progress.cancel();
+            }
+            Ok(string.to_string())
+        }
+
+        fn overflow_count(&self) -> usize {
+            0
+        }
+    }
+
+    #[test]
+    fn cancelling_on_the_last_line_keeps_the_output() {
+        let scratch = Scratch::new("late", "1\n2\n");
+        let (src, dst) = (scratch.path("in.txt"), scratch.path("out.txt"));
+        let progress = Progress::default();
+        let converter = CancelOn { line: "2", progress: &progress };
+        let result = process_file(&src, &dst, false, &converter, &progress);
+        assert!(result.unwrap().is_empty());
+        assert_eq!(fs::read_to_string(dst).unwrap(), "1\n2\n");
+    }
+}
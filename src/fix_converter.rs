@@ -0,0 +1,453 @@
+use std::fmt::{Display, Formatter};
+
+use crate::common_converter::{parse_hex, token_start, Value, ValueCodec, WordCodec};
+use crate::convert_error::{ConvertError, ErrorKind};
+use crate::overflow::{OverflowHandler, OverflowPolicy};
+use crate::rounding::Rounder;
+
+/// Where a sample narrower than its word sits.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Alignment {
+    /// In the low bits; signed samples are written sign-extended.
+    #[default]
+    Lsb,
+    /// In the high bits, with zeros below.
+    Msb,
+}
+
+impl Display for Alignment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Alignment::Lsb => write!(f, "LSB"),
+            Alignment::Msb => write!(f, "MSB"),
+        }
+    }
+}
+
+/// How a signed sample's bits map to integers.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum IntEncoding {
+    #[default]
+    TwosComplement,
+    /// The raw value plus 2^(n-1), so the most negative value is all zeros.
+    OffsetBinary,
+    /// A sign bit above the magnitude; both zeros decode to 0.
+    SignMagnitude,
+    /// Negative values are the bitwise complement of their magnitude.
+    OnesComplement,
+}
+
+impl IntEncoding {
+    pub const ALL: [IntEncoding; 4] = [
+        IntEncoding::TwosComplement,
+        IntEncoding::OffsetBinary,
+        IntEncoding::SignMagnitude,
+        IntEncoding::OnesComplement,
+    ];
+}
+
+impl Display for IntEncoding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IntEncoding::TwosComplement => write!(f, "two's complement"),
+            IntEncoding::OffsetBinary => write!(f, "offset binary"),
+            IntEncoding::SignMagnitude => write!(f, "sign-magnitude"),
+            IntEncoding::OnesComplement => write!(f, "one's complement"),
+        }
+    }
+}
+
+/// Layout of a Q-format fixed-point word: `width` bits in total, optionally
+/// signed in the given `encoding`, with the binary point `frac` bits from
+/// the LSB.
+/// `frac` may be negative or exceed the width, in which case the integer bit
+/// count goes negative or beyond the word.
+///
+/// The sample may occupy only `significant_bits` of the word, e.g. 12-bit
+/// ADC data in 16-bit containers, justified by `alignment`. Decoding ignores
+/// the other bits and sign-extends from the sample's top bit; `frac` counts
+/// from the sample's LSB.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FixFormat {
+    pub width: u32,
+    pub signed: bool,
+    pub frac: i32,
+    pub significant_bits: u32,
+    pub alignment: Alignment,
+    pub encoding: IntEncoding,
+}
+
+impl FixFormat {
+    pub const MIN_WIDTH: u32 = 2;
+    pub const MAX_WIDTH: u32 = 64;
+    /// The range of `frac` offered by the GUI and the command line.
+    pub const MIN_FRAC: i32 = -64;
+    pub const MAX_FRAC: i32 = 128;
+
+    pub fn new(width: u32, signed: bool, frac: i32) -> Self {
+        let width = width.clamp(Self::MIN_WIDTH, Self::MAX_WIDTH);
+        Self {
+            width,
+            signed,
+            frac,
+            significant_bits: width,
+            alignment: Alignment::Lsb,
+            encoding: IntEncoding::TwosComplement,
+        }
+    }
+
+    /// Bits of the word the sample occupies, at most the word width.
+    pub fn significant(&self) -> u32 {
+        self.significant_bits.clamp(1, self.width)
+    }
+
+    pub fn integer_bits(&self) -> i32 {
+        self.significant() as i32 - self.signed as i32 - self.frac
+    }
+
+    pub fn min_raw(&self) -> i128 {
+        if !self.signed {
+            return 0;
+        }
+        match self.encoding {
+            IntEncoding::TwosComplement | IntEncoding::OffsetBinary => -(1 << (self.significant() - 1)),
+            IntEncoding::SignMagnitude | IntEncoding::OnesComplement => -self.max_raw(),
+        }
+    }
+
+    pub fn max_raw(&self) -> i128 {
+        if self.signed {
+            (1 << (self.significant() - 1)) - 1
+        } else {
+            (1 << self.significant()) - 1
+        }
+    }
+
+    /// Rounds `value` to a raw integer of this format and applies the
+    /// overflow policy. An infinity has no low bits to wrap, so wrapping
+    /// rejects it.
+    pub fn quantize(
+        &self,
+        value: &Value,
+        rounder: &Rounder,
+        overflow: &OverflowHandler,
+    ) -> Result<i128, ErrorKind> {
+        if value.is_infinite() && overflow.policy() == OverflowPolicy::Wrap {
+            return Err(ErrorKind::Unrepresentable);
+        }
+        let scaled = value
+            .round_scaled(self.frac, rounder)
+            .ok_or(ErrorKind::Unrepresentable)?;
+        overflow
+            .apply(scaled, self.min_raw(), self.max_raw())
+            .ok_or(ErrorKind::OutOfRange)
+    }
+
+    pub fn hex_digits(&self) -> usize {
+        self.width.div_ceil(4) as usize
+    }
+
+    /// Interprets the sample in the low `width` bits of `bits` as a raw integer.
+    pub fn raw_from_bits(&self, bits: u64) -> i128 {
+        let bits = match self.alignment {
+            Alignment::Lsb => bits,
+            Alignment::Msb => bits >> (self.width - self.significant()),
+        };
+        let field = bits as i128 & ((1 << self.significant()) - 1);
+        if !self.signed {
+            return field;
+        }
+        let half = 1 << (self.significant() - 1);
+        let negative = field >= half;
+        match self.encoding {
+            IntEncoding::TwosComplement if negative => field - 2 * half,
+            IntEncoding::OffsetBinary => field - half,
+            IntEncoding::SignMagnitude if negative => half - field,
+            IntEncoding::OnesComplement if negative => field - (2 * half - 1),
+            _ => field,
+        }
+    }
+
+    /// Bit pattern of `raw` in the format's encoding, placed in a
+    /// `width`-bit word.
+    pub fn bits_from_raw(&self, raw: i128) -> u64 {
+        let half = 1 << (self.significant() - 1);
+        // two's and one's complement fields stay negative, so they are
+        // sign-extended through an LSB-aligned word
+        let field = match self.encoding {
+            _ if !self.signed => raw,
+            IntEncoding::TwosComplement => raw,
+            IntEncoding::OffsetBinary => raw + half,
+            IntEncoding::SignMagnitude if raw < 0 => half - raw,
+            IntEncoding::OnesComplement if raw < 0 => raw - 1,
+            _ => raw,
+        };
+        let field = match self.alignment {
+            Alignment::Lsb => field,
+            Alignment::Msb => field << (self.width - self.significant()),
+        };
+        (field & ((1 << self.width) - 1)) as u64
+    }
+}
+
+impl Default for FixFormat {
+    fn default() -> Self {
+        Self::new(16, true, 15)
+    }
+}
+
+impl Display for FixFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let prefix = if self.signed { "Q" } else { "UQ" };
+        write!(f, "{}{}.{}", prefix, self.integer_bits(), self.frac)
+    }
+}
+
+/// Q-format words. Decoded values stay exact, so converting between two
+/// formats requantizes Qa.b to Qc.d with a single rounding after the binary
+/// point moves, whichever way, then applies the overflow policy.
+pub struct FixCodec {
+    pub format: FixFormat,
+    pub rounder: Rounder,
+    pub overflow: OverflowHandler,
+}
+
+impl FixCodec {
+    /// Rounds `value` to the format, applies the overflow policy and returns
+    /// the bit pattern.
+    fn quantize(&self, value: &Value) -> Result<u64, ErrorKind> {
+        let raw = self.format.quantize(value, &self.rounder, &self.overflow)?;
+        Ok(self.format.bits_from_raw(raw))
+    }
+}
+
+impl WordCodec for FixCodec {
+    fn width(&self) -> u32 {
+        self.format.width
+    }
+
+    fn decode_bits(&self, bits: u64) -> Value {
+        Value::Fixed {
+            raw: self.format.raw_from_bits(bits),
+            frac: self.format.frac,
+        }
+    }
+
+    fn encode_bits(&self, value: &Value) -> Result<u64, ErrorKind> {
+        self.quantize(value)
+    }
+
+    fn overflow_count(&self) -> usize {
+        self.overflow.count()
+    }
+}
+
+impl ValueCodec for FixCodec {
+    fn decode(&self, string: &str) -> Result<Value, ConvertError> {
+        let bits = parse_hex(string, self.format.hex_digits())?;
+        if self.format.width < 64 && bits >> self.format.width != 0 {
+            return Err(ConvertError::new(ErrorKind::OutOfRange, token_start(string).1));
+        }
+        Ok(self.decode_bits(bits))
+    }
+
+    fn encode(&self, value: Value) -> Result<String, ErrorKind> {
+        Ok(format!(
+            "0x{:0width$X}",
+            self.encode_bits(&value)?,
+            width = self.format.hex_digits()
+        ))
+    }
+
+    fn overflow_count(&self) -> usize {
+        self.overflow.count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::overflow::OverflowPolicy;
+    use crate::rounding::RoundingMode;
+    use crate::value_converter_factory::{ConvertOptions, ConverterFactory, ValueType};
+
+    fn fix(width: u32, frac: i32) -> ValueType {
+        ValueType::Fix(FixFormat::new(width, true, frac))
+    }
+
+    /// Converts `input` between the two types and returns the result with
+    /// the overflow count.
+    fn requantize(
+        src: ValueType,
+        dst: ValueType,
+        rounding: RoundingMode,
+        overflow: OverflowPolicy,
+        input: &str,
+    ) -> (String, usize) {
+        let options = ConvertOptions {
+            rounding,
+            overflow,
+            ..ConvertOptions::default()
+        };
+        let converter = ConverterFactory::create(&src, &dst, &options);
+        let output = converter.convert(input).unwrap();
+        (output, converter.overflow_count())
+    }
+
+    #[test]
+    fn requantizes_between_q_formats() {
+        use OverflowPolicy::*;
+        use RoundingMode::*;
+        let cases = [
+            // more fraction bits shift left without rounding
+            (fix(16, 15), fix(32, 31), NearestEven, Saturate, "0x7FFF", "0x7FFF0000", 0),
+            // 0x7FFFFFFF rounds up to 1.0, which Q1.15 cannot hold
+            (fix(32, 31), fix(16, 15), NearestEven, Saturate, "0x7FFFFFFF", "0x7FFF", 1),
+            (fix(32, 31), fix(16, 15), TowardZero, Saturate, "0x7FFFFFFF", "0x7FFF", 0),
+            (fix(32, 31), fix(16, 15), NearestEven, Saturate, "0x00018000", "0x0002", 0),
+            // a 12-bit word prints three digits
+            (fix(16, 15), fix(12, 11), NearestEven, Saturate, "0x4000", "0x400", 0),
+            (fix(16, 15), fix(12, 11), NearestEven, Saturate, "0xC000", "0xC00", 0),
+            // -0.875 is -224 at eight fraction bits, which wraps to 32
+            (fix(16, 15), fix(8, 8), NearestEven, Wrap, "0x9000", "0x20", 1),
+            (fix(16, 15), fix(8, 8), NearestEven, Saturate, "0x9000", "0x80", 1),
+        ];
+        for (src, dst, rounding, overflow, input, output, overflows) in cases {
+            assert_eq!(
+                requantize(src, dst, rounding, overflow, input),
+                (output.to_string(), overflows),
+                "{} -> {} {} {}",
+                src,
+                dst,
+                rounding,
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn maps_each_signed_encoding() {
+        use IntEncoding::*;
+        // the bits of -1, min and max, then a pattern meaning zero
+        for (encoding, min, [minus_one, min_bits, max_bits], zero) in [
+            (TwosComplement, -128, [0xFF, 0x80, 0x7F], 0x00),
+            (OffsetBinary, -128, [0x7F, 0x00, 0xFF], 0x80),
+            (SignMagnitude, -127, [0x81, 0xFF, 0x7F], 0x80),
+            (OnesComplement, -127, [0xFE, 0x80, 0x7F], 0xFF),
+        ] {
+            let format = FixFormat {
+                encoding,
+                ..FixFormat::new(8, true, 0)
+            };
+            assert_eq!((format.min_raw(), format.max_raw()), (min, 127), "{}", encoding);
+            for (raw, bits) in [(-1, minus_one), (min, min_bits), (127, max_bits)] {
+                assert_eq!(format.bits_from_raw(raw), bits, "{} {}", encoding, raw);
+                assert_eq!(format.raw_from_bits(bits), raw, "{} {:#X}", encoding, bits);
+            }
+            assert_eq!(format.raw_from_bits(zero), 0, "{}", encoding);
+        }
+    }
+
+    #[test]
+    fn places_narrow_samples_by_alignment() {
+        let layout = |signed, alignment, encoding| FixFormat {
+            significant_bits: 12,
+            alignment,
+            encoding,
+            ..FixFormat::new(16, signed, 0)
+        };
+        use Alignment::*;
+        use IntEncoding::*;
+        // the word read, its value, and the word written back
+        for (format, cases) in [
+            // two's complement stays sign-extended through the upper bits
+            (
+                layout(true, Lsb, TwosComplement),
+                [
+                    (0x0800, -2048, 0xF800),
+                    (0xFFFF, -1, 0xFFFF),
+                    (0x07FF, 2047, 0x07FF),
+                ],
+            ),
+            (
+                layout(true, Msb, TwosComplement),
+                [
+                    (0x8000, -2048, 0x8000),
+                    (0xFFF0, -1, 0xFFF0),
+                    (0x7FF0, 2047, 0x7FF0),
+                ],
+            ),
+            (
+                layout(true, Lsb, OffsetBinary),
+                [
+                    (0x0000, -2048, 0x0000),
+                    (0x07FF, -1, 0x07FF),
+                    (0x0FFF, 2047, 0x0FFF),
+                ],
+            ),
+            (
+                layout(true, Msb, SignMagnitude),
+                [
+                    (0xFFF0, -2047, 0xFFF0),
+                    (0x8010, -1, 0x8010),
+                    (0x7FF0, 2047, 0x7FF0),
+                ],
+            ),
+            (
+                layout(false, Lsb, TwosComplement),
+                [
+                    (0x0000, 0, 0x0000),
+                    (0x0800, 2048, 0x0800),
+                    (0x0FFF, 4095, 0x0FFF),
+                ],
+            ),
+            (
+                layout(false, Msb, TwosComplement),
+                [
+                    (0x0000, 0, 0x0000),
+                    (0x8000, 2048, 0x8000),
+                    (0xFFF0, 4095, 0xFFF0),
+                ],
+            ),
+        ] {
+            for (bits, raw, written) in cases {
+                assert_eq!(format.raw_from_bits(bits), raw, "{:?} {:#X}", format, bits);
+                assert_eq!(format.bits_from_raw(raw), written, "{:?} {}", format, raw);
+            }
+        }
+        // bits outside the sample are ignored on input
+        assert_eq!(layout(true, Lsb, TwosComplement).raw_from_bits(0x3001), 1);
+        assert_eq!(layout(true, Msb, TwosComplement).raw_from_bits(0x001F), 1);
+        assert_eq!(layout(false, Msb, TwosComplement).max_raw(), 4095);
+    }
+
+    fn quantize(value: Value, policy: OverflowPolicy) -> Result<i128, ErrorKind> {
+        let rounder = Rounder::new(RoundingMode::NearestEven);
+        FixFormat::default().quantize(&value, &rounder, &OverflowHandler::new(policy))
+    }
+
+    #[test]
+    fn wraps_far_shifts_by_low_bits() {
+        let far = Value::Fixed { raw: 3, frac: -5000 };
+        assert_eq!(quantize(far.clone(), OverflowPolicy::Wrap), Ok(0));
+        assert_eq!(quantize(far, OverflowPolicy::Saturate), Ok(0x7FFF));
+        let tiny = Value::Fixed { raw: -3, frac: 5000 };
+        assert_eq!(quantize(tiny, OverflowPolicy::Wrap), Ok(0));
+    }
+
+    #[test]
+    fn rejects_wrapping_infinities() {
+        use OverflowPolicy::*;
+        for (policy, expected) in [
+            (Saturate, Ok(-0x8000)),
+            (Wrap, Err(ErrorKind::Unrepresentable)),
+            (Error, Err(ErrorKind::OutOfRange)),
+        ] {
+            assert_eq!(quantize(Value::Real(f64::NEG_INFINITY), policy), expected, "{}", policy);
+        }
+        assert_eq!(
+            quantize(Value::Real(f64::NAN), Saturate),
+            Err(ErrorKind::Unrepresentable)
+        );
+    }
+}
@@ -1,80 +1,88 @@
-use crate::common_converter::ValueConverter;
+use crate::common_converter::{parse_hex, Value, ValueCodec};
+use crate::convert_error::{ConvertError, ErrorKind};
+use crate::float_format::FloatFormat;
+use crate::rounding::Rounder;
 
-pub struct Float16ToFloatConverter;
+pub struct Float16Codec {
+    pub rounder: Rounder,
+}
 
-impl ValueConverter for Float16ToFloatConverter {
-    fn convert(&self, string: &str) -> String {
-        let bits = u16::from_str_radix(string, 16);
-        match bits {
-            Ok(bits) => {
-                String::from(format!("{}", Float16ToFloatConverter::float16_to_float(bits)))
-            }
-            Err(_) => {
-                println!("Error when parse line: {}", string);
-                String::from("NAN")
-            }
-        }
+impl ValueCodec for Float16Codec {
+    fn decode(&self, string: &str) -> Result<Value, ConvertError> {
+        let bits = parse_hex(string, 4)? as u16;
+        Ok(Value::Real(Self::float16_to_float(bits) as f64))
     }
-}
 
-impl Float16ToFloatConverter {
-    pub fn float16_to_float(bits: u16) -> f32 {
-        f32::from_bits(Float16ToFloat32Converter::float16_to_float32(bits))
+    fn encode(&self, value: Value) -> Result<String, ErrorKind> {
+        let value = FloatFormat::BINARY16.round_value(&value, &self.rounder);
+        Ok(format!("0x{:04X}", Self::float_to_float16(value, &self.rounder)))
     }
 }
 
-pub struct Float16ToFloat32Converter;
-
-impl ValueConverter for Float16ToFloat32Converter {
-    fn convert(&self, string: &str) -> String {
-        let bits = u16::from_str_radix(string, 16);
-        match bits {
-            Ok(bits) => {
-                String::from(format!("{}", Float16ToFloat32Converter::float16_to_float32(bits)))
-            }
-            Err(_) => {
-                println!("Error when parse line: {}", string);
-                String::from("NAN")
-            }
-        }
+impl Float16Codec {
+    pub fn float16_to_float(bits: u16) -> f32 {
+        f32::from_bits(Self::float16_to_float32(bits))
     }
-}
 
-impl Float16ToFloat32Converter {
+    /// Widens an IEEE-754 binary16 value to binary32. Every binary16 value,
+    /// including subnormals, infinities and NaN payloads, is exactly representable.
     pub fn float16_to_float32(bits: u16) -> u32 {
         let sign_bit = ((bits >> 15) & 0x1) as u32;
         let exponent_bits = ((bits >> 10) & 0x1f) as u32;
-        let fraction_bits = (bits & 0x3f) as u32;
-        (sign_bit << 31) | (exponent_bits << 23) | fraction_bits
+        let fraction_bits = (bits & 0x3ff) as u32;
+        match exponent_bits {
+            0 if fraction_bits == 0 => sign_bit << 31,
+            0 => {
+                // subnormal: fraction * 2^-24, renormalized for binary32
+                let shift = fraction_bits.leading_zeros() - 21;
+                let exponent = 127 - 15 + 1 - shift;
+                let fraction = (fraction_bits << shift) & 0x3ff;
+                (sign_bit << 31) | (exponent << 23) | (fraction << 13)
+            }
+            0x1f => (sign_bit << 31) | (0xff << 23) | (fraction_bits << 13),
+            _ => (sign_bit << 31) | ((exponent_bits + 127 - 15) << 23) | (fraction_bits << 13),
+        }
     }
-}
-
-pub struct Float16ToFix32Converter {
-    pub(crate) bit: u32,
-}
 
-impl ValueConverter for Float16ToFix32Converter {
-    fn convert(&self, string: &str) -> String {
-        todo!()
+    /// Encodes a value as IEEE-754 binary16; see `FloatFormat::encode`.
+    pub fn float_to_float16(value: f64, rounder: &Rounder) -> u16 {
+        FloatFormat::BINARY16.encode(value, rounder) as u16
     }
 }
 
-pub struct Float16ToFix16Converter {
-    pub(crate) bit: u32,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rounding::RoundingMode;
 
-impl ValueConverter for Float16ToFix16Converter {
-    fn convert(&self, string: &str) -> String {
-        todo!()
+    #[test]
+    fn widening_matches_float_format() {
+        for bits in 0..=u16::MAX {
+            let widened = Float16Codec::float16_to_float(bits) as f64;
+            let decoded = FloatFormat::BINARY16.decode(bits as u64);
+            if decoded.is_nan() {
+                assert!(widened.is_nan(), "{:04X}", bits);
+            } else {
+                assert_eq!(widened.to_bits(), decoded.to_bits(), "{:04X}", bits);
+            }
+        }
     }
-}
-
-pub struct Float16ToFixComplex16Converter {
-    pub(crate) bit: u32,
-}
 
-impl ValueConverter for Float16ToFixComplex16Converter {
-    fn convert(&self, string: &str) -> String {
-        todo!()
+    #[test]
+    fn golden_values() {
+        let rounder = Rounder::new(RoundingMode::NearestEven);
+        for (value, bits) in [
+            (1.0, 0x3C00),
+            (-2.0, 0xC000),
+            (0.1, 0x2E66),
+            (1.0 / 3.0, 0x3555),
+            (65504.0, 0x7BFF),
+            (65520.0, 0x7C00),
+            (2f64.powi(-24), 0x0001),
+            (2f64.powi(-25), 0x0000),
+            (-0.0, 0x8000),
+        ] {
+            assert_eq!(Float16Codec::float_to_float16(value, &rounder), bits, "{}", value);
+        }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,29 @@
+use crate::common_converter::{parse_hex, Value, ValueCodec};
+use crate::convert_error::{ConvertError, ErrorKind};
+use crate::float_format::FloatFormat;
+use crate::rounding::Rounder;
+
+/// IEEE-754 binary64, which is also the precision of `Value::Real`. Wider
+/// integer and fixed-point values round with the selected mode.
+pub struct Float64Codec {
+    pub rounder: Rounder,
+}
+
+impl ValueCodec for Float64Codec {
+    fn decode(&self, string: &str) -> Result<Value, ConvertError> {
+        let bits = parse_hex(string, 16)?;
+        Ok(Value::Real(f64::from_bits(bits)))
+    }
+
+    fn encode(&self, value: Value) -> Result<String, ErrorKind> {
+        let bits = match value.real_part() {
+            // already binary64, down to any NaN payload
+            Value::Real(value) => value.to_bits(),
+            value => {
+                let value = FloatFormat::BINARY64.round_value(&value, &self.rounder);
+                FloatFormat::BINARY64.encode(value, &self.rounder)
+            }
+        };
+        Ok(format!("0x{:016X}", bits))
+    }
+}
@@ -1,102 +1,24 @@
-use std::str::FromStr;
-use crate::common_converter::ValueConverter;
-
-pub struct FloatToFloat32Converter;
-
-impl ValueConverter for FloatToFloat32Converter {
-    fn convert(&self, string: &str) -> String {
-        let value = f32::from_str(string);
-        match value {
-            Ok(value) => {
-                String::from(format!("0x{:08X}", value.to_bits()))
-            }
-            Err(_) => {
-                println!("Error when parse line: {}", string);
-                String::from("NAN")
-            }
-        }
-    }
-}
-
-pub struct FloatToFloat16Converter;
-
-impl ValueConverter for FloatToFloat16Converter {
-    fn convert(&self, string: &str) -> String {
-        let value = f32::from_str(string);
-        match value {
-            Ok(value) => {
-                String::from(format!("0x{:04X}", value.to_bits()))
-            }
-            Err(_) => {
-                println!("Error when parse line: {}", string);
-                String::from("NAN")
-            }
-        }
-    }
-}
-
-pub struct FloatToFix16Converter {
-    pub(crate) bit: u32,
-}
-
-impl ValueConverter for FloatToFix16Converter {
-    fn convert(&self, string: &str) -> String {
-        let value = f32::from_str(string);
-        match value {
-            Ok(value) => {
-                let value = (value * 2f32.powi(self.bit as i32)).round() as i32;
-                String::from(format!("0x{:04X}", value))
-            }
-            Err(_) => {
-                println!("Error when parse line: {}", string);
-                String::from("NAN")
-            }
-        }
-    }
-}
-
-pub struct FloatToFix32Converter {
-    pub(crate) bit: u32,
-}
-
-impl ValueConverter for FloatToFix32Converter {
-    fn convert(&self, string: &str) -> String {
-        let value = f64::from_str(string);
-        match value {
-            Ok(value) => {
-                let value = (value * 2f64.powi(self.bit as i32)).round() as i32;
-                String::from(format!("0x{:08X}", value))
-            }
-            Err(_) => {
-                println!("Error when parse line: {}", string);
-                String::from("NAN")
-            }
-        }
+use crate::common_converter::{parse_decimal, token_start, Value, ValueCodec};
+use crate::convert_error::{ConvertError, ErrorKind};
+use crate::exact::{fixed_to_decimal, Decimal};
+
+/// Decimal text. Input keeps its exact digits for fixed-point destinations,
+/// and fixed-point sources print their exact expansion.
+pub struct FloatCodec;
+
+impl ValueCodec for FloatCodec {
+    fn decode(&self, string: &str) -> Result<Value, ConvertError> {
+        let value = parse_decimal(string)?;
+        Ok(match Decimal::parse(token_start(string).0, value) {
+            Some(decimal) => Value::Decimal(decimal),
+            None => Value::Real(value),
+        })
     }
-}
 
-pub struct FloatToFixComplex16Converter {
-    pub(crate) bit: u32,
-}
-
-impl ValueConverter for FloatToFixComplex16Converter {
-    fn convert(&self, string: &str) -> String {
-        todo!()
+    fn encode(&self, value: Value) -> Result<String, ErrorKind> {
+        Ok(match value {
+            Value::Fixed { raw, frac } => fixed_to_decimal(raw, frac),
+            value => format!("{}", value.real()),
+        })
     }
 }
-
-pub struct FloatToComplex16Converter;
-
-impl ValueConverter for FloatToComplex16Converter {
-    fn convert(&self, string: &str) -> String {
-        todo!()
-    }
-}
-
-pub struct FloatToComplexConverter;
-
-impl ValueConverter for FloatToComplexConverter {
-    fn convert(&self, string: &str) -> String {
-        todo!()
-    }
-}
\ No newline at end of file
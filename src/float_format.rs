@@ -0,0 +1,566 @@
+use std::fmt::{Display, Formatter};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use num_traits::ToPrimitive;
+
+use crate::common_converter::{parse_hex, token_start, Value, ValueCodec, WordCodec};
+use crate::convert_error::{ConvertError, ErrorKind};
+use crate::rounding::{Rounder, RoundingMode};
+
+/// Which encodings of a float format are reserved for non-finite values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Specials {
+    /// The all-ones exponent holds infinity (zero fraction) and NaNs.
+    Ieee,
+    /// Only the all-ones exponent and fraction pattern is NaN; there is no
+    /// infinity, so the all-ones exponent otherwise holds finite values.
+    NanOnly,
+    /// Every encoding is a finite number.
+    Finite,
+}
+
+/// A binary floating-point format with an optional sign bit, a biased
+/// exponent and an implicit leading significand bit. Without `subnormals`
+/// the zero exponent field encodes only zero and tiny values flush to zero
+/// or the smallest normal number.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FloatFormat {
+    pub signed: bool,
+    pub exponent_bits: u32,
+    pub fraction_bits: u32,
+    pub bias: i32,
+    pub specials: Specials,
+    pub subnormals: bool,
+}
+
+impl FloatFormat {
+    pub const BINARY16: FloatFormat = FloatFormat::ieee(5, 10);
+    pub const BINARY32: FloatFormat = FloatFormat::ieee(8, 23);
+    pub const BINARY64: FloatFormat = FloatFormat::ieee(11, 52);
+    pub const BFLOAT16: FloatFormat = FloatFormat::ieee(8, 7);
+    /// OCP FP8 E4M3: no infinities, a single NaN pattern, largest value 448.
+    pub const E4M3: FloatFormat = FloatFormat {
+        specials: Specials::NanOnly,
+        ..FloatFormat::ieee(4, 3)
+    };
+    /// OCP FP8 E5M2: IEEE-754 conventions, largest value 57344.
+    pub const E5M2: FloatFormat = FloatFormat::ieee(5, 2);
+    /// NVIDIA TensorFloat-32, stored in the low 19 bits of a word.
+    pub const TF32: FloatFormat = FloatFormat::ieee(8, 10);
+
+    pub const MAX_EXPONENT_BITS: u32 = 11;
+    pub const MAX_FRACTION_BITS: u32 = 52;
+
+    /// A format following IEEE-754 conventions with the standard bias.
+    pub const fn ieee(exponent_bits: u32, fraction_bits: u32) -> Self {
+        Self {
+            signed: true,
+            exponent_bits,
+            fraction_bits,
+            bias: Self::standard_bias(exponent_bits),
+            specials: Specials::Ieee,
+            subnormals: true,
+        }
+    }
+
+    /// `2^(exponent_bits - 1) - 1`, centring the exponent range on 1.
+    pub const fn standard_bias(exponent_bits: u32) -> i32 {
+        (1 << (exponent_bits - 1)) - 1
+    }
+
+    /// The biases that keep every value of the format, from the smallest
+    /// subnormal to the largest finite number, a binary64, so that `decode`
+    /// is exact. Empty when the exponent field is too wide for that.
+    pub fn bias_range(&self) -> RangeInclusive<i32> {
+        // the largest exponent at most 1023 and the smallest subnormal,
+        // 2^(1 - bias - fraction_bits), at least 2^-1074
+        self.max_exponent_field() - 1023..=1075 - self.fraction_bits as i32
+    }
+
+    /// The widest fraction for which `bias_range` is not empty.
+    pub fn max_fraction_bits(&self) -> u32 {
+        let bits = 1075 + 1023 - self.max_exponent_field();
+        bits.clamp(1, Self::MAX_FRACTION_BITS as i32) as u32
+    }
+
+    /// The exponent field of the largest finite magnitude.
+    fn max_exponent_field(&self) -> i32 {
+        let all_ones = (1 << self.exponent_bits) - 1;
+        match self.specials {
+            Specials::Ieee => all_ones - 1,
+            Specials::NanOnly | Specials::Finite => all_ones,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.signed as u32 + self.exponent_bits + self.fraction_bits
+    }
+
+    pub fn has_nan(&self) -> bool {
+        self.specials != Specials::Finite
+    }
+
+    fn sign_bit(&self) -> u64 {
+        if self.signed {
+            1 << (self.exponent_bits + self.fraction_bits)
+        } else {
+            0
+        }
+    }
+
+    fn exponent_mask(&self) -> u64 {
+        ((1 << self.exponent_bits) - 1) << self.fraction_bits
+    }
+
+    fn fraction_mask(&self) -> u64 {
+        (1 << self.fraction_bits) - 1
+    }
+
+    /// Encoding of the largest finite magnitude.
+    fn max_finite(&self) -> u64 {
+        match self.specials {
+            Specials::Ieee => self.exponent_mask() - 1,
+            Specials::NanOnly => self.exponent_mask() + self.fraction_mask() - 1,
+            Specials::Finite => self.exponent_mask() | self.fraction_mask(),
+        }
+    }
+
+    /// Unbiased exponent of the largest finite magnitude.
+    fn max_exponent(&self) -> i32 {
+        self.max_exponent_field() - self.bias
+    }
+
+    /// Encoding of the magnitude a value takes when it overflows without saturating.
+    fn infinity_or_nan(&self) -> u64 {
+        match self.specials {
+            Specials::Ieee => self.exponent_mask(),
+            Specials::NanOnly => self.exponent_mask() | self.fraction_mask(),
+            Specials::Finite => self.max_finite(),
+        }
+    }
+
+    /// Encodes `value` with the given rounding. Values beyond the largest
+    /// finite number overflow to infinity or saturate depending on the
+    /// rounding direction, and tiny values underflow gradually through the
+    /// subnormals. Formats without infinities overflow to NaN, and formats
+    /// without NaN saturate. Unsigned formats take negative values to NaN
+    /// when they have one and to zero otherwise; NaN encodes as zero in
+    /// formats without NaN.
+    pub fn encode(&self, value: f64, rounder: &Rounder) -> u64 {
+        self.encode_with(value, rounder, false)
+    }
+
+    /// Like `encode`, but infinities and overflowing values become the
+    /// largest finite number of the same sign.
+    pub fn encode_saturating(&self, value: f64, rounder: &Rounder) -> u64 {
+        self.encode_with(value, rounder, true)
+    }
+
+    /// The binary64 that an exact fixed-point or decimal `value`, or the real
+    /// part of a complex one, rounds to in this format, which `encode` then
+    /// stores without rounding a second time. Other values, and exact ones
+    /// whose result binary64 cannot hold, are returned as `value.real()` for
+    /// `encode` to round.
+    pub fn round_value(&self, value: &Value, rounder: &Rounder) -> f64 {
+        if let Value::Complex(real, _) = value {
+            return self.round_value(real, rounder);
+        }
+        let approx = value.real();
+        let exact = matches!(value, Value::Fixed { .. } | Value::Decimal(_));
+        if !exact || !approx.is_finite() {
+            return approx;
+        }
+        let fraction_bits = self.fraction_bits as i32;
+        let min_exponent = 1 - self.bias;
+        let mut exponent = if approx == 0.0 {
+            // zero, or below binary64's range and so below any normal number
+            // the result could hold
+            min_exponent - 1
+        } else {
+            binary_exponent(approx)
+        };
+        if exponent >= min_exponent {
+            // the binary64 approximation may have rounded up to a power of two
+            let truncate = Rounder::new(RoundingMode::TowardZero);
+            let truncated = value.round_scaled(fraction_bits - exponent, &truncate);
+            if truncated.is_some_and(|truncated| truncated.bits() <= fraction_bits as u64) {
+                exponent -= 1;
+            }
+        }
+        let quantum = if exponent < min_exponent && !self.subnormals {
+            // the grid {0, smallest normal}
+            min_exponent
+        } else {
+            exponent.max(min_exponent) - fraction_bits
+        };
+        if !(-1074..=1023 - 53).contains(&quantum) {
+            return approx;
+        }
+        match value.round_scaled(-quantum, rounder).and_then(|rounded| rounded.to_f64()) {
+            // the significand has at most 53 bits, so the product is exact
+            Some(rounded) => (rounded * pow2(quantum)).copysign(approx),
+            None => approx,
+        }
+    }
+
+    fn encode_with(&self, value: f64, rounder: &Rounder, saturate: bool) -> u64 {
+        let negative = value.is_sign_negative();
+        let sign_bit = if negative { self.sign_bit() } else { 0 };
+        if value.is_nan() {
+            return sign_bit | self.nan(value);
+        }
+        if !self.signed && negative {
+            return if value == 0.0 || saturate {
+                0
+            } else {
+                self.nan(value)
+            };
+        }
+        if value.is_infinite() {
+            return if saturate {
+                sign_bit | self.max_finite()
+            } else {
+                sign_bit | self.infinity_or_nan()
+            };
+        }
+        if value == 0.0 {
+            return sign_bit;
+        }
+
+        let bits = value.to_bits();
+        let exponent_bits = ((bits >> 52) & 0x7ff) as i32;
+        let fraction_bits = bits & 0x000f_ffff_ffff_ffff;
+        let (exponent, significand) = if exponent_bits == 0 {
+            (-1022, fraction_bits)
+        } else {
+            (exponent_bits - 1023, fraction_bits | (1 << 52))
+        };
+
+        let min_exponent = 1 - self.bias;
+        if exponent > self.max_exponent() {
+            return sign_bit | self.overflow(rounder, negative, saturate);
+        }
+        let shift = 52 - self.fraction_bits + (min_exponent - exponent).max(0) as u32;
+
+        let magnitude = if exponent < min_exponent && !self.subnormals {
+            // round onto the grid {0, smallest normal}
+            let rounded = rounder.round_shifted(significand, shift + self.fraction_bits, negative);
+            rounded << self.fraction_bits
+        } else if exponent < min_exponent {
+            // a carry out of the subnormal range yields the smallest normal
+            rounder.round_shifted(significand, shift, negative)
+        } else {
+            let rounded = rounder.round_shifted(significand, shift, negative);
+            // the implicit bit in `rounded` bumps the exponent field by one,
+            // and a rounding carry propagates into it naturally
+            (((exponent + self.bias - 1) as u64) << self.fraction_bits) + rounded
+        };
+        if magnitude > self.max_finite() {
+            return sign_bit | self.overflow(rounder, negative, saturate);
+        }
+        sign_bit | magnitude
+    }
+
+    fn nan(&self, value: f64) -> u64 {
+        match self.specials {
+            Specials::Ieee => {
+                // keep the upper payload bits and force a quiet NaN
+                let payload = (value.to_bits() & 0x000f_ffff_ffff_ffff) >> (52 - self.fraction_bits);
+                self.exponent_mask() | (1 << (self.fraction_bits - 1)) | payload
+            }
+            Specials::NanOnly => self.exponent_mask() | self.fraction_mask(),
+            Specials::Finite => 0,
+        }
+    }
+
+    fn overflow(&self, rounder: &Rounder, negative: bool, saturate: bool) -> u64 {
+        if !saturate && rounder.overflows_to_infinity(negative) {
+            self.infinity_or_nan()
+        } else {
+            self.max_finite()
+        }
+    }
+
+    /// Decodes `bits`; exact for every format that fits within binary64.
+    pub fn decode(&self, bits: u64) -> f64 {
+        let sign = if bits & self.sign_bit() != 0 { -1.0 } else { 1.0 };
+        let magnitude_bits = bits & (self.exponent_mask() | self.fraction_mask());
+        let exponent_field = (magnitude_bits >> self.fraction_bits) as i32;
+        let fraction = bits & self.fraction_mask();
+        let all_ones = self.exponent_mask() >> self.fraction_bits;
+        let magnitude = match self.specials {
+            Specials::Ieee if exponent_field as u64 == all_ones => {
+                // a NaN keeps its payload in the upper binary64 fraction bits
+                f64::from_bits(0x7ff0_0000_0000_0000 | fraction << (52 - self.fraction_bits))
+            }
+            Specials::NanOnly if magnitude_bits == self.infinity_or_nan() => f64::NAN,
+            // a zero fraction is zero whatever the scale
+            _ if exponent_field == 0 && (fraction == 0 || !self.subnormals) => 0.0,
+            _ if exponent_field == 0 => {
+                fraction as f64 * pow2(1 - self.bias - self.fraction_bits as i32)
+            }
+            _ => {
+                let significand = (fraction | (1 << self.fraction_bits)) as f64;
+                significand * pow2(exponent_field - self.bias - self.fraction_bits as i32)
+            }
+        };
+        magnitude.copysign(sign)
+    }
+}
+
+impl Default for FloatFormat {
+    fn default() -> Self {
+        Self::BINARY16
+    }
+}
+
+/// Written as `e<exponent bits>m<fraction bits>` followed by whichever of
+/// `unsigned`, `bias=<n>`, `nan-only`, `finite` and `no-subnormals` differ
+/// from IEEE-754 conventions, separated by commas, e.g. `e4m3,nan-only`.
+impl Display for FloatFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "e{}m{}", self.exponent_bits, self.fraction_bits)?;
+        if !self.signed {
+            write!(f, ",unsigned")?;
+        }
+        if self.bias != Self::standard_bias(self.exponent_bits) {
+            write!(f, ",bias={}", self.bias)?;
+        }
+        match self.specials {
+            Specials::Ieee => {}
+            Specials::NanOnly => write!(f, ",nan-only")?,
+            Specials::Finite => write!(f, ",finite")?,
+        }
+        if !self.subnormals {
+            write!(f, ",no-subnormals")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for FloatFormat {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let mut parts = string.split(',').map(str::trim);
+        let widths = parts.next().unwrap_or_default();
+        let (exponent_bits, fraction_bits) = widths
+            .strip_prefix('e')
+            .and_then(|widths| widths.split_once('m'))
+            .and_then(|(e, m)| Some((e.parse::<u32>().ok()?, m.parse::<u32>().ok()?)))
+            .ok_or_else(|| format!("expected e<bits>m<bits>, got {}", widths))?;
+        if !(1..=Self::MAX_EXPONENT_BITS).contains(&exponent_bits) {
+            return Err(format!("exponent must be 1 to {} bits", Self::MAX_EXPONENT_BITS));
+        }
+        if !(1..=Self::MAX_FRACTION_BITS).contains(&fraction_bits) {
+            return Err(format!("mantissa must be 1 to {} bits", Self::MAX_FRACTION_BITS));
+        }
+        let mut format = Self::ieee(exponent_bits, fraction_bits);
+        for part in parts {
+            match part {
+                "unsigned" => format.signed = false,
+                "nan-only" => format.specials = Specials::NanOnly,
+                "finite" => format.specials = Specials::Finite,
+                "no-subnormals" => format.subnormals = false,
+                _ => {
+                    format.bias = part
+                        .strip_prefix("bias=")
+                        .and_then(|bias| bias.parse().ok())
+                        .ok_or_else(|| format!("unknown float format option {}", part))?;
+                }
+            }
+        }
+        let biases = format.bias_range();
+        if biases.is_empty() {
+            return Err(format!("{} has too wide a range for binary64", widths));
+        }
+        if !biases.contains(&format.bias) {
+            return Err(format!("bias must be {} to {}", biases.start(), biases.end()));
+        }
+        Ok(format)
+    }
+}
+
+/// floor(log2 |value|) of a finite, nonzero binary64.
+fn binary_exponent(value: f64) -> i32 {
+    let bits = value.to_bits();
+    match ((bits >> 52) & 0x7ff) as i32 {
+        0 => -1011 - (bits & 0x000f_ffff_ffff_ffff).leading_zeros() as i32,
+        exponent => exponent - 1023,
+    }
+}
+
+/// 2^exponent, exact over the whole binary64 range including subnormals.
+fn pow2(exponent: i32) -> f64 {
+    if exponent < -1022 {
+        2f64.powi(exponent + 64) * 2f64.powi(-64)
+    } else {
+        2f64.powi(exponent)
+    }
+}
+
+/// Hex codec for any `FloatFormat`, optionally saturating on overflow.
+pub struct FloatFormatCodec {
+    pub format: FloatFormat,
+    pub saturate: bool,
+    pub rounder: Rounder,
+}
+
+impl WordCodec for FloatFormatCodec {
+    fn width(&self) -> u32 {
+        self.format.width()
+    }
+
+    fn decode_bits(&self, bits: u64) -> Value {
+        Value::Real(self.format.decode(bits))
+    }
+
+    fn encode_bits(&self, value: &Value) -> Result<u64, ErrorKind> {
+        let value = self.format.round_value(value, &self.rounder);
+        if value.is_nan() && !self.format.has_nan() {
+            return Err(ErrorKind::Unrepresentable);
+        }
+        Ok(if self.saturate {
+            self.format.encode_saturating(value, &self.rounder)
+        } else {
+            self.format.encode(value, &self.rounder)
+        })
+    }
+}
+
+impl ValueCodec for FloatFormatCodec {
+    fn decode(&self, string: &str) -> Result<Value, ConvertError> {
+        let digits = self.format.width().div_ceil(4) as usize;
+        let bits = parse_hex(string, digits)?;
+        if bits.checked_shr(self.format.width()).unwrap_or(0) != 0 {
+            return Err(ConvertError::new(ErrorKind::OutOfRange, token_start(string).1));
+        }
+        Ok(self.decode_bits(bits))
+    }
+
+    fn encode(&self, value: Value) -> Result<String, ErrorKind> {
+        let bits = self.encode_bits(&value)?;
+        let digits = self.format.width().div_ceil(4) as usize;
+        Ok(format!("0x{:0digits$X}", bits, digits = digits))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exact::Decimal;
+
+    fn encode(format: FloatFormat, value: f64) -> u64 {
+        format.encode(value, &Rounder::new(RoundingMode::NearestEven))
+    }
+
+    #[test]
+    fn binary16_subnormal_boundaries() {
+        let format = FloatFormat::BINARY16;
+        assert_eq!(format.decode(0x0001), 2f64.powi(-24));
+        assert_eq!(encode(format, 2f64.powi(-24)), 0x0001);
+        // halfway between zero and the smallest subnormal ties to even
+        assert_eq!(encode(format, 2f64.powi(-25)), 0x0000);
+        assert_eq!(encode(format, 2f64.powi(-25) * 1.5), 0x0001);
+        assert_eq!(encode(format, -2f64.powi(-25)), 0x8000);
+        assert_eq!(format.decode(0x03FF), 1023.0 * 2f64.powi(-24));
+        assert_eq!(encode(format, 2f64.powi(-14)), 0x0400);
+    }
+
+    #[test]
+    fn binary16_overflow() {
+        let format = FloatFormat::BINARY16;
+        assert_eq!(encode(format, 65504.0), 0x7BFF);
+        assert_eq!(encode(format, 65519.99), 0x7BFF);
+        assert_eq!(encode(format, 65520.0), 0x7C00);
+        assert_eq!(encode(format, -65520.0), 0xFC00);
+        let toward_zero = Rounder::new(RoundingMode::TowardZero);
+        assert_eq!(format.encode(65520.0, &toward_zero), 0x7BFF);
+        assert_eq!(format.encode_saturating(f64::INFINITY, &toward_zero), 0x7BFF);
+    }
+
+    #[test]
+    fn binary16_nan_and_signed_zero() {
+        let format = FloatFormat::BINARY16;
+        let nan = format.decode(0x7E01);
+        assert!(nan.is_nan());
+        assert_eq!(encode(format, nan), 0x7E01);
+        // a signalling NaN keeps its payload and becomes quiet
+        assert_eq!(encode(format, format.decode(0x7C01)), 0x7E01);
+        assert_eq!(encode(format, format.decode(0xFE00)), 0xFE00);
+        assert_eq!(format.decode(0x7C00), f64::INFINITY);
+        let zero = format.decode(0x8000);
+        assert_eq!(zero, 0.0);
+        assert!(zero.is_sign_negative());
+        assert_eq!(encode(format, -0.0), 0x8000);
+    }
+
+    #[test]
+    fn e4m3_rounding_at_the_top() {
+        let format = FloatFormat::E4M3;
+        assert_eq!(format.decode(0x7E), 448.0);
+        assert!(format.decode(0x7F).is_nan());
+        assert_eq!(encode(format, 448.0), 0x7E);
+        // halfway to 480 ties to the even 448
+        assert_eq!(encode(format, 464.0), 0x7E);
+        assert_eq!(encode(format, 470.0), 0x7F);
+        assert_eq!(encode(format, -470.0), 0xFF);
+        let rounder = Rounder::new(RoundingMode::NearestEven);
+        assert_eq!(format.encode_saturating(470.0, &rounder), 0x7E);
+    }
+
+    #[test]
+    fn e5m2_overflow() {
+        let format = FloatFormat::E5M2;
+        assert_eq!(format.decode(0x7B), 57344.0);
+        assert_eq!(encode(format, 57344.0), 0x7B);
+        assert_eq!(encode(format, 61440.0), 0x7C);
+        assert_eq!(format.decode(0x7C), f64::INFINITY);
+    }
+
+    #[test]
+    fn spec_round_trip() {
+        for spec in ["e5m10", "e4m3,nan-only", "e8m10,unsigned,bias=100,finite,no-subnormals"] {
+            assert_eq!(spec.parse::<FloatFormat>().unwrap().to_string(), spec);
+        }
+        assert!("e12m3".parse::<FloatFormat>().is_err());
+    }
+
+    #[test]
+    fn biases_keep_formats_within_binary64() {
+        let format = |spec: &str| spec.parse::<FloatFormat>();
+        // the largest exponent reaches 30 + 993 and the smallest subnormal
+        // 2^(1 - 1065 - 10)
+        assert_eq!(format("e5m10,bias=-993").unwrap().decode(0x7BFF), 2047.0 * 2f64.powi(1013));
+        assert_eq!(format("e5m10,bias=1065").unwrap().decode(0x0001), f64::from_bits(1));
+        assert!(format("e5m10,bias=-994").is_err());
+        assert!(format("e5m10,bias=1066").is_err());
+        assert!(format("e5m10,bias=-2000").is_err());
+        // the all-ones exponent holds finite values, one binade too many
+        assert!(format("e11m52,nan-only").is_err());
+        assert!(format("e11m51,nan-only,bias=1024").is_ok());
+        assert_eq!(FloatFormat::BINARY64.bias_range(), 1023..=1023);
+        // a zero field is zero even where the scale overflows
+        let extreme = FloatFormat { bias: -2000, ..FloatFormat::BINARY16 };
+        assert_eq!(extreme.decode(0x0000), 0.0);
+        assert!(extreme.decode(0x8000).is_sign_negative());
+    }
+
+    #[test]
+    fn exact_values_round_once() {
+        let toward_zero = Rounder::new(RoundingMode::TowardZero);
+        let encode_exact = |format: FloatFormat, value: Value| {
+            format.encode(format.round_value(&value, &toward_zero), &toward_zero)
+        };
+        let token = "0.99999999999999999";
+        let decimal = Value::Decimal(Decimal::parse(token, token.parse().unwrap()).unwrap());
+        assert_eq!(encode_exact(FloatFormat::BINARY16, decimal), 0x3BFF);
+        let raw = 0x1000_001F_FFFF_FFFF;
+        assert_eq!(encode_exact(FloatFormat::BINARY32, Value::Fixed { raw, frac: 0 }), 0x5D80_0000);
+        let binary64 = encode_exact(FloatFormat::BINARY64, Value::Fixed { raw, frac: 0 });
+        assert_eq!(binary64, 0x43B0_0000_1FFF_FFFF);
+        let tiny = Value::Fixed { raw: -1, frac: 30 };
+        assert_eq!(encode_exact(FloatFormat::BINARY16, tiny), 0x8000);
+    }
+}
@@ -0,0 +1,82 @@
+use std::fmt::{Display, Formatter};
+
+use crate::common_converter::{parse_binary, parse_hex, token_start, Value, ValueCodec};
+use crate::convert_error::{ConvertError, ErrorKind};
+use crate::fix_converter::FixFormat;
+use crate::overflow::OverflowHandler;
+use crate::rounding::Rounder;
+
+/// How an integer is written: two's-complement hex or binary words, or a
+/// signed decimal number.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Radix {
+    #[default]
+    Hex,
+    Decimal,
+    Binary,
+}
+
+impl Display for Radix {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Radix::Hex => write!(f, "hex"),
+            Radix::Decimal => write!(f, "dec"),
+            Radix::Binary => write!(f, "bin"),
+        }
+    }
+}
+
+/// Plain integers of `width` bits.
+pub struct IntCodec {
+    /// The integer layout, a fixed-point format without fraction bits.
+    pub format: FixFormat,
+    pub radix: Radix,
+    pub rounder: Rounder,
+    pub overflow: OverflowHandler,
+}
+
+impl IntCodec {
+    fn parse(&self, string: &str) -> Result<i128, ConvertError> {
+        let (token, column) = token_start(string);
+        let bits = match self.radix {
+            Radix::Hex => parse_hex(string, self.format.hex_digits())?,
+            Radix::Binary => parse_binary(string, self.format.width as usize)?,
+            Radix::Decimal => {
+                let raw = token
+                    .parse::<i128>()
+                    .map_err(|_| ConvertError::new(ErrorKind::InvalidNumber, column))?;
+                if !(self.format.min_raw()..=self.format.max_raw()).contains(&raw) {
+                    return Err(ConvertError::new(ErrorKind::OutOfRange, column));
+                }
+                return Ok(raw);
+            }
+        };
+        if self.format.width < 64 && bits >> self.format.width != 0 {
+            return Err(ConvertError::new(ErrorKind::OutOfRange, column));
+        }
+        Ok(self.format.raw_from_bits(bits))
+    }
+}
+
+impl ValueCodec for IntCodec {
+    fn decode(&self, string: &str) -> Result<Value, ConvertError> {
+        Ok(Value::Fixed {
+            raw: self.parse(string)?,
+            frac: 0,
+        })
+    }
+
+    fn encode(&self, value: Value) -> Result<String, ErrorKind> {
+        let raw = self.format.quantize(&value, &self.rounder, &self.overflow)?;
+        let bits = self.format.bits_from_raw(raw);
+        Ok(match self.radix {
+            Radix::Hex => format!("0x{:0width$X}", bits, width = self.format.hex_digits()),
+            Radix::Binary => format!("0b{:0width$b}", bits, width = self.format.width as usize),
+            Radix::Decimal => raw.to_string(),
+        })
+    }
+
+    fn overflow_count(&self) -> usize {
+        self.overflow.count()
+    }
+}
@@ -0,0 +1,30 @@
+//! Conversion between textual representations of numeric formats: decimal,
+//! IEEE floats, bfloat16, FP8, integers, Q-format fixed point, affine
+//! quantized integers and complex numbers.
+//!
+//! Every `ValueType` decodes to and encodes from a canonical `Value`, and
+//! `ConverterFactory` composes the two sides into a `ValueConverter`.
+
+pub mod bfloat16_converter;
+pub mod byte_order;
+pub mod common_converter;
+pub mod complex_pair_converter;
+pub mod complex_converter;
+pub mod convert_error;
+pub mod exact;
+pub mod file_converter;
+pub mod fix_converter;
+pub mod float16_converter;
+pub mod float32_converter;
+pub mod float64_converter;
+pub mod float_converter;
+pub mod float_format;
+pub mod int_converter;
+pub mod overflow;
+pub mod quantized_converter;
+pub mod rounding;
+pub mod value_converter_factory;
+
+pub use common_converter::{Value, ValueCodec, ValueConverter};
+pub use convert_error::{ConvertError, ErrorKind};
+pub use value_converter_factory::{ConvertOptions, ConverterFactory, ValueType};
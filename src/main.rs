@@ -1,30 +1,31 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader, Write};
+use std::io;
 use std::path::Path;
-use std::str::FromStr;
+use std::sync::Arc;
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
 use eframe::{egui, Frame};
 use eframe::egui::{Context, Ui};
 use rfd::FileDialog;
 
-use crate::common_converter::ValueConverter;
-use crate::float_converter::FloatToFloat32Converter;
-use crate::value_converter_factory::{ConverterFactory, ValueConverterFactory, ValueType};
-use crate::value_converter_factory::ValueType::{
-    Complex, Complex16, Fix16, Fix32, Float, Float16, Float32,
+use bit_converter::common_converter::ValueConverter;
+use bit_converter::convert_error::ConvertError;
+use bit_converter::file_converter::{output_path, process_file, Progress, DEFAULT_NAME_TEMPLATE};
+use bit_converter::byte_order::ByteOrder;
+use bit_converter::complex_converter::{ComplexStyle, MagnitudeUnit, PhaseUnit};
+use bit_converter::complex_pair_converter::{IqOrder, Packing, ScalarFormat};
+use bit_converter::fix_converter::{Alignment, FixFormat, IntEncoding};
+use bit_converter::float_format::{FloatFormat, Specials};
+use bit_converter::int_converter::Radix;
+use bit_converter::overflow::OverflowPolicy;
+use bit_converter::rounding::RoundingMode;
+use bit_converter::value_converter_factory::{ConvertOptions, ConverterFactory, ValueType};
+use bit_converter::value_converter_factory::ValueType::{
+    BFloat16, Complex, ComplexPair, CustomFloat, Fix, Float, Float16, Float32,
+    Float64, Fp8E4M3, Fp8E5M2, Int, Quantized,
 };
 
-mod common_converter;
-mod complex16_converter;
-mod complex_converter;
-mod fix16_converter;
-mod fix32_converter;
-mod fix_complex16_converter;
-mod float16_converter;
-mod float32_converter;
-mod float_converter;
-mod value_converter_factory;
-
 fn main() -> Result<(), eframe::Error> {
     // 创建视口选项，设置视口的内部大小为320x240像素
     let options = eframe::NativeOptions {
@@ -45,94 +46,100 @@ fn main() -> Result<(), eframe::Error> {
     )
 }
 
+const MAX_SHOWN_ERRORS: usize = 10;
+
+/// A file conversion running on a worker thread, which hands back the
+/// result and the overflow count of its converter.
+struct FileJob {
+    progress: Arc<Progress>,
+    started: Instant,
+    handle: JoinHandle<(io::Result<Vec<ConvertError>>, usize)>,
+}
+
 struct BitConverter {
     converter: Box<dyn ValueConverter>,
     src_file: String,
     dst_file: String,
+    name_template: String,
+    confirm_overwrite: bool,
+    file_job: Option<FileJob>,
     src_value: String,
     dst_value: String,
-    src_bit: u32,
-    dst_bit: u32,
     src: ValueType,
     dst: ValueType,
+    options: ConvertOptions,
+    overflow_count: usize,
+    file_status: String,
+    errors: Vec<ConvertError>,
 }
 
 impl BitConverter {
-    fn new(cc: &eframe::CreationContext<'_>) -> Self {
+    fn new(_cc: &eframe::CreationContext<'_>) -> Self {
         Self {
-            converter: Box::new(FloatToFloat32Converter),
+            converter: ConverterFactory::create(&Float, &Float32, &ConvertOptions::default()),
             src_file: "".to_string(),
             dst_file: "".to_string(),
+            name_template: DEFAULT_NAME_TEMPLATE.to_string(),
+            confirm_overwrite: false,
+            file_job: None,
             src_value: "".to_string(),
             dst_value: "".to_string(),
-            src_bit: 0,
-            dst_bit: 0,
             src: Float,
             dst: Float32,
+            options: ConvertOptions::default(),
+            overflow_count: 0,
+            file_status: "".to_string(),
+            errors: Vec::new(),
         }
     }
 
     fn switch_converter(&mut self) {
-        self.converter = ConverterFactory::create(&self.src, &self.dst, self.src_bit, self.dst_bit);
+        self.converter = ConverterFactory::create(&self.src, &self.dst, &self.options);
     }
 
     fn grid_contents(&mut self, ui: &mut Ui) {
         let mut switch_converter = false;
-        let src = self.src;
-        let dst = self.dst;
-        let src_bit = self.src_bit;
-        let dst_bit = self.dst_bit;
 
         ui.label("Input Data Type:");
         ui.horizontal(|ui| {
-            egui::ComboBox::new("input_data_type", "")
-                .selected_text(format!("{}", self.src))
-                .show_ui(ui, |ui| {
-                    for kind in [Float, Float32, Float16, Fix32, Fix16, Complex, Complex16] {
-                        switch_converter |= ui
-                            .selectable_value(&mut self.src, kind, format!("{}", kind))
-                            .changed();
-                    }
-                });
-            if self.src == dst {
-                self.dst = src;
-                self.src_bit = dst_bit;
-                self.dst_bit = src_bit;
-            }
-            match self.src {
-                Fix32 => self.select_src_bit(&mut switch_converter, ui,32),
-                Fix16 => self.select_src_bit(&mut switch_converter, ui,16),
-                _ => {}
+            switch_converter |= Self::select_value_type(ui, "input_data_type", &mut self.src);
+            if self.src.is_hex() {
+                let order = &mut self.options.src_byte_order;
+                switch_converter |= Self::select_byte_order(ui, "input_byte_order", order);
             }
         });
         ui.end_row();
 
         ui.label("Output Data Type:");
         ui.horizontal(|ui| {
-            egui::ComboBox::new("output_data_type", "")
-                .selected_text(format!("{}", self.dst))
-                .show_ui(ui, |ui| {
-                    for kind in [Float, Float32, Float16, Fix32, Fix16, Complex, Complex16] {
-                        if ConverterFactory::check(&self.src, &kind) {
-                            switch_converter |= ui
-                                .selectable_value(&mut self.dst, kind, format!("{}", kind))
-                                .changed();
-                        }
-                    }
-                });
-            match self.dst {
-                Fix32 => self.select_src_bit(&mut switch_converter, ui,32),
-                Fix16 => self.select_src_bit(&mut switch_converter, ui,16),
-                _ => {}
+            switch_converter |= Self::select_value_type(ui, "output_data_type", &mut self.dst);
+            if self.dst.is_hex() {
+                let order = &mut self.options.dst_byte_order;
+                switch_converter |= Self::select_byte_order(ui, "output_byte_order", order);
             }
         });
         ui.end_row();
 
-        if switch_converter && ConverterFactory::check(&self.src, &self.dst) {
+        ui.label("Rounding:");
+        ui.horizontal(|ui| {
+            switch_converter |= Self::select_rounding(ui, &mut self.options.rounding);
+        });
+        ui.end_row();
+
+        ui.label("Overflow:");
+        egui::ComboBox::new("overflow", "")
+            .selected_text(format!("{}", self.options.overflow))
+            .show_ui(ui, |ui| {
+                for policy in [OverflowPolicy::Saturate, OverflowPolicy::Wrap, OverflowPolicy::Error] {
+                    switch_converter |= ui
+                        .selectable_value(&mut self.options.overflow, policy, format!("{}", policy))
+                        .changed();
+                }
+            });
+        ui.end_row();
+
+        if switch_converter {
             self.switch_converter();
-        } else {
-            self.src = src;
-            self.dst = dst;
         }
 
         ui.label("Convert File:");
@@ -145,27 +152,68 @@ impl BitConverter {
                 .add_filter("text", &["txt", "dat"])
                 .set_directory("~")
                 .pick_file();
-            match file {
-                None => {}
-                Some(file) => {
-                    self.src_file = file.clone().to_str().unwrap_or("").to_string();
-                    let mut file_name = file.clone().file_name().unwrap().to_os_string().into_string().unwrap_or(String::new());
-                    file_name.insert_str(file_name.rfind(".").unwrap_or(0),"_out");
-                    self.dst_file = file.clone().parent().unwrap().join(file_name).to_str().unwrap_or("").to_string();
-                }
+            if let Some(file) = file {
+                self.src_file = file.to_string_lossy().into_owned();
+                self.dst_file = self.default_dst_file();
             }
         }
         ui.end_row();
 
+        ui.label("Output Name Template:");
+        if ui.text_edit_singleline(&mut self.name_template).changed() && !self.src_file.is_empty() {
+            self.dst_file = self.default_dst_file();
+        }
+        ui.end_row();
+
         ui.label("Output File Path:");
         ui.text_edit_singleline(&mut self.dst_file);
         ui.end_row();
 
-        if ui.button("Convert").clicked() {
-            process_file(self.src_file.as_ref(), self.converter.as_ref());
+        self.poll_file_job();
+        if let Some(job) = &self.file_job {
+            if ui.button("Cancel").clicked() {
+                job.progress.cancel();
+            }
+            let progress = &job.progress;
+            let text = format!("{} lines, {} bytes", progress.lines(), progress.bytes());
+            match progress.fraction() {
+                Some(fraction) => ui.add(egui::ProgressBar::new(fraction).text(text)),
+                None => ui.label(text),
+            };
+            ui.ctx().request_repaint_after(Duration::from_millis(100));
+        } else {
+            if ui.button("Convert").clicked() {
+                if self.dst_file.is_empty() {
+                    self.dst_file = self.default_dst_file();
+                }
+                if Path::new(&self.dst_file).exists() {
+                    self.confirm_overwrite = true;
+                } else {
+                    self.convert_file(false);
+                }
+            }
+            ui.label(&self.file_status);
         }
         ui.end_row();
 
+        if self.confirm_overwrite {
+            egui::Window::new("Overwrite file?")
+                .collapsible(false)
+                .resizable(false)
+                .show(ui.ctx(), |ui| {
+                    ui.label(format!("{} already exists.", self.dst_file));
+                    ui.horizontal(|ui| {
+                        if ui.button("Overwrite").clicked() {
+                            self.confirm_overwrite = false;
+                            self.convert_file(true);
+                        }
+                        if ui.button("Cancel").clicked() {
+                            self.confirm_overwrite = false;
+                        }
+                    });
+                });
+        }
+
         ui.label("Convert Value:");
         ui.end_row();
 
@@ -178,46 +226,517 @@ impl BitConverter {
         ui.end_row();
 
         if ui.button("Convert").clicked() {
-            let src_values = self.src_value.split("\n");
+            self.switch_converter();
             self.dst_value = String::new();
-            for src_value in src_values {
-                if src_value.starts_with("0x") || src_value.starts_with("0X") {
-                    self.dst_value.push_str(&format!("{}\n", self.converter.convert(&src_value[2..])));
-                } else {
-                    self.dst_value.push_str(&format!("{}\n", self.converter.convert(&src_value)));
+            self.errors.clear();
+            for (index, src_value) in self.src_value.lines().enumerate() {
+                if src_value.trim().is_empty() {
+                    self.dst_value.push('\n');
+                    continue;
+                }
+                match self.converter.convert(src_value) {
+                    Ok(value) => self.dst_value.push_str(&format!("{}\n", value)),
+                    Err(e) => {
+                        let e = e.at_line(index + 1);
+                        self.dst_value.push_str(&format!("error: {}\n", e.kind));
+                        self.errors.push(e);
+                    }
                 }
             }
+            self.overflow_count = self.converter.overflow_count();
         }
         ui.end_row();
+
+        ui.label(format!("Overflowed samples: {}", self.overflow_count));
+        ui.end_row();
+
+        ui.label(format!("Errors: {}", self.errors.len()));
+        ui.vertical(|ui| {
+            for e in self.errors.iter().take(MAX_SHOWN_ERRORS) {
+                ui.label(format!("{}", e));
+            }
+            if self.errors.len() > MAX_SHOWN_ERRORS {
+                ui.label(format!("... and {} more", self.errors.len() - MAX_SHOWN_ERRORS));
+            }
+        });
+        ui.end_row();
+    }
+
+    fn default_dst_file(&self) -> String {
+        output_path(&self.name_template, &self.src_file, &self.src, &self.dst)
+    }
+
+    fn convert_file(&mut self, overwrite: bool) {
+        // a fresh converter also restarts stochastic rounding from its seed
+        let converter = ConverterFactory::create(&self.src, &self.dst, &self.options);
+        let progress = Arc::new(Progress::default());
+        let worker_progress = progress.clone();
+        let src = self.src_file.clone();
+        let dst = self.dst_file.clone();
+        let handle = thread::spawn(move || {
+            let result = process_file(&src, &dst, overwrite, converter.as_ref(), &worker_progress);
+            (result, converter.overflow_count())
+        });
+        self.file_job = Some(FileJob {
+            progress,
+            started: Instant::now(),
+            handle,
+        });
+    }
+
+    fn poll_file_job(&mut self) {
+        if !self.file_job.as_ref().is_some_and(|job| job.handle.is_finished()) {
+            return;
+        }
+        let job = self.file_job.take().unwrap();
+        let elapsed = job.started.elapsed().as_secs_f64();
+        let lines = job.progress.lines();
+        self.errors.clear();
+        match job.handle.join() {
+            Ok((Ok(errors), overflow_count)) => {
+                self.file_status = format!(
+                    "{} lines converted, {} errors in {:.2} s",
+                    lines - errors.len(),
+                    errors.len(),
+                    elapsed
+                );
+                self.errors = errors;
+                self.overflow_count = overflow_count;
+            }
+            Ok((Err(e), _)) if e.kind() == io::ErrorKind::Interrupted => {
+                self.file_status = format!("cancelled after {} lines", lines);
+            }
+            Ok((Err(e), _)) => self.file_status = e.to_string(),
+            Err(_) => self.file_status = "conversion failed".to_string(),
+        }
+    }
+
+    fn select_value_type(ui: &mut Ui, id: &str, value_type: &mut ValueType) -> bool {
+        let mut changed = false;
+        let fix_format = match value_type {
+            Fix(format) => *format,
+            _ => FixFormat::default(),
+        };
+        let order = match value_type {
+            ComplexPair { order, .. } => *order,
+            _ => IqOrder::default(),
+        };
+        let saturate = match value_type {
+            Fp8E4M3 { saturate } | Fp8E5M2 { saturate } | CustomFloat { saturate, .. } => *saturate,
+            _ => false,
+        };
+        let format = match value_type {
+            CustomFloat { format, .. } => *format,
+            _ => FloatFormat::default(),
+        };
+        let radix = match value_type {
+            Int { radix, .. } | Quantized { radix, .. } => *radix,
+            _ => Radix::default(),
+        };
+        let int = |width, signed| Int {
+            format: FixFormat::new(width, signed, 0),
+            radix,
+        };
+        let style = match value_type {
+            Complex { style } => *style,
+            _ => ComplexStyle::default(),
+        };
+        let kinds = [
+            Float,
+            Float64,
+            Float32,
+            Float16,
+            BFloat16,
+            Fp8E4M3 { saturate },
+            Fp8E5M2 { saturate },
+            CustomFloat { format, saturate },
+            int(8, true),
+            int(16, true),
+            int(32, true),
+            int(64, true),
+            int(8, false),
+            int(16, false),
+            int(32, false),
+            int(64, false),
+            match value_type {
+                Quantized { .. } => *value_type,
+                _ => Quantized {
+                    storage: FixFormat::new(8, true, 0),
+                    radix,
+                    scale: 1.0,
+                    zero_point: 0,
+                },
+            },
+            Fix(fix_format),
+            Complex { style },
+            match value_type {
+                ComplexPair { .. } => *value_type,
+                _ => ComplexPair {
+                    component: ScalarFormat::default(),
+                    order,
+                    packing: Packing::default(),
+                },
+            },
+        ];
+        egui::ComboBox::new(id, "")
+            .selected_text(format!("{}", value_type))
+            .show_ui(ui, |ui| {
+                for kind in kinds {
+                    changed |= ui
+                        .selectable_value(value_type, kind, format!("{}", kind))
+                        .changed();
+                }
+            });
+        match value_type {
+            Fix(format) => {
+                changed |= Self::select_fix_format(ui, id, format, FixFormat::MAX_WIDTH);
+            }
+            ComplexPair {
+                component,
+                order,
+                packing,
+            } => {
+                changed |= Self::select_component(ui, id, component);
+                changed |= Self::select_order(ui, id, order);
+                if component.width() <= Packing::MAX_ONE_WORD_COMPONENT_WIDTH {
+                    egui::ComboBox::new(format!("{}_packing", id), "")
+                        .selected_text(format!("{}", packing))
+                        .show_ui(ui, |ui| {
+                            for choice in [Packing::OneWord, Packing::TwoWords] {
+                                changed |= ui
+                                    .selectable_value(packing, choice, format!("{}", choice))
+                                    .changed();
+                            }
+                        });
+                } else {
+                    ui.label(format!("{}", Packing::TwoWords));
+                }
+            }
+            Fp8E4M3 { saturate } | Fp8E5M2 { saturate } => {
+                changed |= ui.checkbox(saturate, "saturate").changed();
+            }
+            CustomFloat { format, saturate } => {
+                changed |= Self::select_float_format(ui, id, format);
+                changed |= ui.checkbox(saturate, "saturate").changed();
+            }
+            Complex { style } => {
+                egui::ComboBox::new(format!("{}_style", id), "")
+                    .selected_text(format!("{}", style))
+                    .show_ui(ui, |ui| {
+                        for choice in ComplexStyle::ALL {
+                            changed |= ui
+                                .selectable_value(style, choice, format!("{}", choice))
+                                .changed();
+                        }
+                    });
+                if let ComplexStyle::PolarColumns { magnitude, phase } = style {
+                    changed |= Self::select_polar_columns(ui, id, magnitude, phase);
+                }
+            }
+            Int { format, radix } => {
+                changed |= Self::select_radix(ui, id, radix);
+                changed |= Self::select_sample_layout(ui, id, format);
+            }
+            Quantized {
+                storage,
+                radix,
+                scale,
+                zero_point,
+            } => {
+                egui::ComboBox::new(format!("{}_storage", id), "")
+                    .selected_text(format!("{} bit", storage.width))
+                    .show_ui(ui, |ui| {
+                        for width in [8, 16, 32] {
+                            let format = FixFormat::new(width, storage.signed, 0);
+                            changed |= ui
+                                .selectable_value(storage, format, format!("{} bit", width))
+                                .changed();
+                        }
+                    });
+                changed |= ui.checkbox(&mut storage.signed, "signed").changed();
+                changed |= Self::select_radix(ui, id, radix);
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(scale)
+                            .range(f64::MIN_POSITIVE..=f64::MAX)
+                            .speed(0.001)
+                            .prefix("scale: "),
+                    )
+                    .changed();
+                changed |= ui
+                    .add(egui::DragValue::new(zero_point).prefix("zero point: "))
+                    .changed();
+                changed |= Self::select_sample_layout(ui, id, storage);
+            }
+            _ => {}
+        }
+        changed
+    }
+
+    fn select_radix(ui: &mut Ui, id: &str, radix: &mut Radix) -> bool {
+        let mut changed = false;
+        egui::ComboBox::new(format!("{}_radix", id), "")
+            .selected_text(format!("{}", radix))
+            .show_ui(ui, |ui| {
+                for choice in [Radix::Hex, Radix::Decimal, Radix::Binary] {
+                    changed |= ui
+                        .selectable_value(radix, choice, format!("{}", choice))
+                        .changed();
+                }
+            });
+        changed
     }
 
-    fn select_src_bit(&mut self, switch_converter: &mut bool, ui: &mut Ui, range: u32) {
-        egui::ComboBox::new("src_bit", "")
-            .selected_text(format!("{}", self.src_bit))
+    fn select_order(ui: &mut Ui, id: &str, order: &mut IqOrder) -> bool {
+        let mut changed = false;
+        egui::ComboBox::new(format!("{}_order", id), "")
+            .selected_text(format!("{}", order))
             .show_ui(ui, |ui| {
-                for kind in 0..range {
-                    *switch_converter |= ui
-                        .selectable_value(&mut self.src_bit, kind, format!("{}", kind))
+                for choice in [IqOrder::IHigh, IqOrder::QHigh] {
+                    changed |= ui
+                        .selectable_value(order, choice, format!("{}", choice))
                         .changed();
                 }
             });
+        changed
+    }
+
+    /// Picks a complex component from the hex scalar types, then edits its format.
+    fn select_component(ui: &mut Ui, id: &str, component: &mut ScalarFormat) -> bool {
+        let mut changed = false;
+        let presets = [
+            Float64,
+            Float32,
+            Float16,
+            BFloat16,
+            Fp8E4M3 { saturate: false },
+            Fp8E5M2 { saturate: false },
+            Int {
+                format: FixFormat::new(16, true, 0),
+                radix: Radix::Hex,
+            },
+            Int {
+                format: FixFormat::new(32, true, 0),
+                radix: Radix::Hex,
+            },
+            Fix(FixFormat::default()),
+        ];
+        egui::ComboBox::new(format!("{}_component", id), "")
+            .selected_text(format!("{}", component))
+            .show_ui(ui, |ui| {
+                for preset in presets {
+                    if let Some(format) = preset.scalar_format() {
+                        changed |= ui
+                            .selectable_value(component, format, format!("{}", preset))
+                            .changed();
+                    }
+                }
+            });
+        match component {
+            ScalarFormat::Float { format, saturate } => {
+                changed |= Self::select_float_format(ui, &format!("{}_component", id), format);
+                changed |= ui.checkbox(saturate, "saturate").changed();
+            }
+            ScalarFormat::Fix(format) => {
+                let id = format!("{}_component", id);
+                changed |= Self::select_fix_format(ui, &id, format, FixFormat::MAX_WIDTH);
+            }
+        }
+        changed
+    }
+
+    fn select_polar_columns(
+        ui: &mut Ui,
+        id: &str,
+        magnitude: &mut MagnitudeUnit,
+        phase: &mut PhaseUnit,
+    ) -> bool {
+        let mut changed = false;
+        // at least one column stays on
+        egui::ComboBox::new(format!("{}_magnitude", id), "")
+            .selected_text(format!("{}", magnitude))
+            .show_ui(ui, |ui| {
+                for choice in [MagnitudeUnit::Off, MagnitudeUnit::Linear, MagnitudeUnit::Decibel] {
+                    if choice != MagnitudeUnit::Off || *phase != PhaseUnit::Off {
+                        changed |= ui
+                            .selectable_value(magnitude, choice, format!("{}", choice))
+                            .changed();
+                    }
+                }
+            });
+        egui::ComboBox::new(format!("{}_phase", id), "")
+            .selected_text(format!("{}", phase))
+            .show_ui(ui, |ui| {
+                for choice in [PhaseUnit::Off, PhaseUnit::Radians, PhaseUnit::Degrees] {
+                    if choice != PhaseUnit::Off || *magnitude != MagnitudeUnit::Off {
+                        changed |= ui
+                            .selectable_value(phase, choice, format!("{}", choice))
+                            .changed();
+                    }
+                }
+            });
+        changed
     }
 
-    fn select_dst_bit(&mut self, switch_converter: &mut bool, ui: &mut Ui, range: u32) {
-        egui::ComboBox::new("dst_bit", "")
-            .selected_text(format!("{}", self.dst_bit))
+    fn select_fix_format(ui: &mut Ui, id: &str, format: &mut FixFormat, max_width: u32) -> bool {
+        let mut width_changed = false;
+        egui::ComboBox::new(format!("{}_width", id), "")
+            .selected_text(format!("{} bit", format.width))
             .show_ui(ui, |ui| {
-                for kind in 0..range {
-                    *switch_converter |= ui
-                        .selectable_value(&mut self.dst_bit, kind, format!("{}", kind))
+                for width in FixFormat::MIN_WIDTH..=max_width {
+                    width_changed |= ui
+                        .selectable_value(&mut format.width, width, format!("{} bit", width))
                         .changed();
                 }
             });
+        if width_changed {
+            format.significant_bits = format.width;
+        }
+        let mut changed = width_changed;
+        changed |= ui.checkbox(&mut format.signed, "signed").changed();
+        changed |= ui
+            .add(
+                egui::DragValue::new(&mut format.frac)
+                    .range(FixFormat::MIN_FRAC..=FixFormat::MAX_FRAC)
+                    .prefix("frac: "),
+            )
+            .changed();
+        changed |= Self::select_sample_layout(ui, id, format);
+        ui.label(format!("{}", format));
+        changed
+    }
+
+    /// Significant bits of the word, their alignment and the signed encoding.
+    fn select_sample_layout(ui: &mut Ui, id: &str, format: &mut FixFormat) -> bool {
+        let mut changed = ui
+            .add(
+                egui::DragValue::new(&mut format.significant_bits)
+                    .range(1..=format.width)
+                    .prefix("sig: "),
+            )
+            .changed();
+        if format.significant_bits < format.width {
+            egui::ComboBox::new(format!("{}_alignment", id), "")
+                .selected_text(format!("{}", format.alignment))
+                .show_ui(ui, |ui| {
+                    for alignment in [Alignment::Lsb, Alignment::Msb] {
+                        changed |= ui
+                            .selectable_value(&mut format.alignment, alignment, format!("{}", alignment))
+                            .changed();
+                    }
+                });
+        }
+        if format.signed {
+            egui::ComboBox::new(format!("{}_encoding", id), "")
+                .selected_text(format!("{}", format.encoding))
+                .show_ui(ui, |ui| {
+                    for encoding in IntEncoding::ALL {
+                        changed |= ui
+                            .selectable_value(&mut format.encoding, encoding, format!("{}", encoding))
+                            .changed();
+                    }
+                });
+        }
+        changed
+    }
+
+    fn select_float_format(ui: &mut Ui, id: &str, format: &mut FloatFormat) -> bool {
+        let mut changed = ui.checkbox(&mut format.signed, "sign").changed();
+        let exponent = ui.add(
+            egui::DragValue::new(&mut format.exponent_bits)
+                .range(1..=FloatFormat::MAX_EXPONENT_BITS)
+                .prefix("exp: "),
+        );
+        if exponent.changed() {
+            format.bias = FloatFormat::standard_bias(format.exponent_bits);
+            changed = true;
+        }
+        let max_fraction_bits = format.max_fraction_bits();
+        changed |= ui
+            .add(
+                egui::DragValue::new(&mut format.fraction_bits)
+                    .range(1..=max_fraction_bits)
+                    .prefix("man: "),
+            )
+            .changed();
+        // egui clamps the bias into range, keeping the format within binary64
+        let biases = format.bias_range();
+        changed |= ui
+            .add(
+                egui::DragValue::new(&mut format.bias)
+                    .range(biases)
+                    .prefix("bias: "),
+            )
+            .changed();
+        egui::ComboBox::new(format!("{}_specials", id), "")
+            .selected_text(Self::specials_name(format.specials))
+            .show_ui(ui, |ui| {
+                for specials in [Specials::Ieee, Specials::NanOnly, Specials::Finite] {
+                    changed |= ui
+                        .selectable_value(&mut format.specials, specials, Self::specials_name(specials))
+                        .changed();
+                }
+            });
+        changed |= ui.checkbox(&mut format.subnormals, "subnormals").changed();
+        ui.label(format!("{} bit", format.width()));
+        changed
+    }
+
+    fn specials_name(specials: Specials) -> &'static str {
+        match specials {
+            Specials::Ieee => "Inf + NaN",
+            Specials::NanOnly => "NaN only",
+            Specials::Finite => "finite",
+        }
+    }
+
+    fn select_byte_order(ui: &mut Ui, id: &str, order: &mut ByteOrder) -> bool {
+        let mut changed = false;
+        egui::ComboBox::new(id, "")
+            .selected_text(format!("{}", order))
+            .show_ui(ui, |ui| {
+                for choice in ByteOrder::ALL {
+                    changed |= ui
+                        .selectable_value(order, choice, format!("{}", choice))
+                        .changed();
+                }
+            });
+        changed
+    }
+
+    fn select_rounding(ui: &mut Ui, rounding: &mut RoundingMode) -> bool {
+        let mut changed = false;
+        let stochastic = match rounding {
+            RoundingMode::Stochastic(seed) => RoundingMode::Stochastic(*seed),
+            _ => RoundingMode::Stochastic(0),
+        };
+        egui::ComboBox::new("rounding", "")
+            .selected_text(format!("{}", rounding))
+            .show_ui(ui, |ui| {
+                for mode in [
+                    RoundingMode::NearestEven,
+                    RoundingMode::NearestAway,
+                    RoundingMode::TowardZero,
+                    RoundingMode::Floor,
+                    RoundingMode::Ceil,
+                    stochastic,
+                ] {
+                    changed |= ui
+                        .selectable_value(rounding, mode, format!("{}", mode))
+                        .changed();
+                }
+            });
+        if let RoundingMode::Stochastic(seed) = rounding {
+            changed |= ui
+                .add(egui::DragValue::new(seed).prefix("seed: "))
+                .changed();
+        }
+        changed
     }
 }
 
 impl eframe::App for BitConverter {
-    fn update(&mut self, ctx: &Context, frame: &mut Frame) {
+    fn update(&mut self, ctx: &Context, _frame: &mut Frame) {
         egui::CentralPanel::default().show(ctx, |ui| {
             egui::Grid::new("grid")
                 .num_columns(2)
@@ -229,44 +748,3 @@ impl eframe::App for BitConverter {
         });
     }
 }
-
-fn process_file(path: &str, converter: &dyn ValueConverter) {
-    let path = Path::new(path);
-    let file = File::open(path);
-    match file {
-        Ok(file) => {
-            let buffered = BufReader::new(file);
-            let file_name = path.file_name().unwrap().to_str().unwrap();
-            let file_dir = path.parent().unwrap_or(".".as_ref());
-            let file_extension = path.extension().unwrap_or("".as_ref()).to_str().unwrap();
-            let mut file_out = File::create(Path::join(
-                file_dir,
-                format!(
-                    "{}_out.{}",
-                    &file_name[..(file_name.len() - file_extension.len() - 1)],
-                    file_extension
-                ),
-            ))
-            .unwrap();
-            for line in buffered.lines() {
-                match line {
-                    Ok(line) => {
-                        let value;
-                        if line.starts_with("0x") {
-                            value = converter.convert(&line[2..]);
-                        } else {
-                            value = converter.convert(&line);
-                        }
-                        file_out
-                            .write_all(format!("{}\n", value).as_bytes())
-                            .unwrap();
-                    }
-                    Err(e) => println!("{}", e),
-                }
-            }
-        }
-        Err(e) => {
-            println!("{}", e);
-        }
-    }
-}
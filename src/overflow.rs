@@ -0,0 +1,68 @@
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use num_bigint::BigInt;
+use num_traits::{Euclid, ToPrimitive};
+
+/// What a quantizing conversion does with values outside the target range.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum OverflowPolicy {
+    #[default]
+    Saturate,
+    /// Keeps the low bits, as two's-complement hardware does.
+    Wrap,
+    /// Rejects the sample.
+    Error,
+}
+
+impl Display for OverflowPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OverflowPolicy::Saturate => write!(f, "saturate"),
+            OverflowPolicy::Wrap => write!(f, "wrap"),
+            OverflowPolicy::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// Applies an `OverflowPolicy` and counts the samples it had to touch.
+pub struct OverflowHandler {
+    policy: OverflowPolicy,
+    count: AtomicUsize,
+}
+
+impl OverflowHandler {
+    pub fn new(policy: OverflowPolicy) -> Self {
+        Self {
+            policy,
+            count: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    /// Brings `raw` into `min..=max`, or returns `None` when the policy
+    /// rejects out-of-range samples. `raw` is unbounded, so wrapping keeps
+    /// the low bits of values shifted far beyond the format.
+    pub fn apply(&self, raw: BigInt, min: i128, max: i128) -> Option<i128> {
+        if let Some(raw) = raw.to_i128().filter(|raw| (min..=max).contains(raw)) {
+            return Some(raw);
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        match self.policy {
+            OverflowPolicy::Saturate if raw < BigInt::from(min) => Some(min),
+            OverflowPolicy::Saturate => Some(max),
+            OverflowPolicy::Wrap => {
+                let offset = (raw - min).rem_euclid(&(BigInt::from(max) - min + 1));
+                Some(min + offset.to_i128().expect("below the range size"))
+            }
+            OverflowPolicy::Error => None,
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::Relaxed)
+    }
+}
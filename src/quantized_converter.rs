@@ -0,0 +1,31 @@
+use crate::common_converter::{Value, ValueCodec};
+use crate::convert_error::{ConvertError, ErrorKind};
+use crate::int_converter::IntCodec;
+
+/// Affine quantization as used for ML tensors: an integer `q` stands for
+/// `scale * (q - zero_point)`. Encoding rounds `value / scale` before adding
+/// the zero point, then applies the storage's overflow policy.
+pub struct QuantizedCodec {
+    pub storage: IntCodec,
+    pub scale: f64,
+    pub zero_point: i64,
+}
+
+impl ValueCodec for QuantizedCodec {
+    fn decode(&self, string: &str) -> Result<Value, ConvertError> {
+        let q = self.storage.decode(string)?.real();
+        Ok(Value::Real(self.scale * (q - self.zero_point as f64)))
+    }
+
+    fn encode(&self, value: Value) -> Result<String, ErrorKind> {
+        if value.real().is_nan() {
+            return Err(ErrorKind::Unrepresentable);
+        }
+        let q = self.storage.rounder.round(value.real() / self.scale) + self.zero_point as f64;
+        self.storage.encode(Value::Real(q))
+    }
+
+    fn overflow_count(&self) -> usize {
+        self.storage.overflow_count()
+    }
+}
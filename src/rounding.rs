@@ -0,0 +1,149 @@
+use std::cmp;
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// How a quantizing conversion picks between the two representable
+/// neighbours of a value.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RoundingMode {
+    #[default]
+    NearestEven,
+    NearestAway,
+    TowardZero,
+    Floor,
+    Ceil,
+    /// Rounds up with probability equal to the discarded fraction, drawing
+    /// from a generator seeded with the given value.
+    Stochastic(u64),
+}
+
+impl Display for RoundingMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RoundingMode::NearestEven => write!(f, "nearest even"),
+            RoundingMode::NearestAway => write!(f, "nearest away"),
+            RoundingMode::TowardZero => write!(f, "toward zero"),
+            RoundingMode::Floor => write!(f, "floor"),
+            RoundingMode::Ceil => write!(f, "ceil"),
+            RoundingMode::Stochastic(_) => write!(f, "stochastic"),
+        }
+    }
+}
+
+/// Applies a `RoundingMode`, carrying the generator state for stochastic rounding.
+pub struct Rounder {
+    mode: RoundingMode,
+    state: AtomicU64,
+}
+
+impl Rounder {
+    pub fn new(mode: RoundingMode) -> Self {
+        let seed = match mode {
+            RoundingMode::Stochastic(seed) => seed,
+            _ => 0,
+        };
+        Self {
+            mode,
+            state: AtomicU64::new(seed),
+        }
+    }
+
+    /// Rounds `value` to an integer.
+    pub fn round(&self, value: f64) -> f64 {
+        match self.mode {
+            RoundingMode::NearestEven => value.round_ties_even(),
+            RoundingMode::NearestAway => value.round(),
+            RoundingMode::TowardZero => value.trunc(),
+            RoundingMode::Floor => value.floor(),
+            RoundingMode::Ceil => value.ceil(),
+            RoundingMode::Stochastic(_) => {
+                let floor = value.floor();
+                if self.uniform() < value - floor {
+                    floor + 1.0
+                } else {
+                    floor
+                }
+            }
+        }
+    }
+
+    /// Rounds the magnitude `significand / 2^shift` of a sign-magnitude
+    /// encoding to an integer, where `negative` is the sign of the value.
+    pub fn round_shifted(&self, significand: u64, shift: u32, negative: bool) -> u64 {
+        if shift == 0 {
+            return significand;
+        }
+        // anything shifted this far is below half an ulp either way
+        let shift = shift.min(100);
+        let significand = significand as u128;
+        let truncated = (significand >> shift) as u64;
+        let remainder = significand & ((1 << shift) - 1);
+        if remainder == 0 {
+            return truncated;
+        }
+        let half = 1 << (shift - 1);
+        let round_up = match self.mode {
+            RoundingMode::NearestEven => {
+                remainder > half || (remainder == half && truncated & 1 == 1)
+            }
+            RoundingMode::NearestAway => remainder >= half,
+            RoundingMode::TowardZero => false,
+            RoundingMode::Floor => negative,
+            RoundingMode::Ceil => !negative,
+            RoundingMode::Stochastic(_) => {
+                let sample = self.next_random() as u128;
+                let sample = if shift < 64 {
+                    sample & ((1 << shift) - 1)
+                } else {
+                    sample << (shift - 64)
+                };
+                sample < remainder
+            }
+        };
+        truncated + round_up as u64
+    }
+
+    /// Whether a magnitude with a nonzero discarded fraction rounds up, given
+    /// how that fraction compares with one half, whether the truncated
+    /// magnitude is odd, and the fraction itself for stochastic rounding.
+    pub fn rounds_up(&self, half: cmp::Ordering, odd: bool, fraction: f64, negative: bool) -> bool {
+        match self.mode {
+            RoundingMode::NearestEven => {
+                half == cmp::Ordering::Greater || (half == cmp::Ordering::Equal && odd)
+            }
+            RoundingMode::NearestAway => half != cmp::Ordering::Less,
+            RoundingMode::TowardZero => false,
+            RoundingMode::Floor => negative,
+            RoundingMode::Ceil => !negative,
+            RoundingMode::Stochastic(_) => self.uniform() < fraction,
+        }
+    }
+
+    /// Whether a value too large for a format rounds to infinity rather than
+    /// to the largest finite value.
+    pub fn overflows_to_infinity(&self, negative: bool) -> bool {
+        match self.mode {
+            RoundingMode::TowardZero => false,
+            RoundingMode::Floor => negative,
+            RoundingMode::Ceil => !negative,
+            _ => true,
+        }
+    }
+
+    /// 53 random bits as a uniform sample in [0, 1).
+    fn uniform(&self) -> f64 {
+        (self.next_random() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// splitmix64
+    fn next_random(&self) -> u64 {
+        let state = self
+            .state
+            .fetch_add(0x9e37_79b9_7f4a_7c15, Ordering::Relaxed)
+            .wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
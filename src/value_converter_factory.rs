@@ -1,238 +1,323 @@
 use std::fmt::{Display, Formatter};
 
-use crate::common_converter::{SelfConverter, ValueConverter};
-use crate::complex16_converter::Complex16ToComplexConverter;
-use crate::complex_converter::{ComplexToComplex16Converter, ComplexToFloat32Converter};
-use crate::fix16_converter::{Fix16ToFloat16Converter, Fix16ToFloatConverter};
-use crate::fix32_converter::{Fix32ToFloat32Converter, Fix32ToFloatConverter};
-use crate::float16_converter::{
-    Float16ToFix16Converter, Float16ToFix32Converter, Float16ToFloat32Converter,
-    Float16ToFloatConverter,
-};
-use crate::float32_converter::{
-    Float32ToComplexConverter, Float32ToFix16Converter, Float32ToFix32Converter,
-    Float32ToFloat16Converter, Float32ToFloatConverter,
-};
-use crate::float_converter::{
-    FloatToFix16Converter, FloatToFix32Converter, FloatToFloat16Converter, FloatToFloat32Converter,
-};
+use crate::bfloat16_converter::BFloat16Codec;
+use crate::byte_order::{ByteOrder, ByteOrderCodec};
+use crate::common_converter::{PivotConverter, ValueCodec, ValueConverter, WordCodec};
+use crate::complex_converter::{ComplexCodec, ComplexStyle};
+use crate::complex_pair_converter::{ComplexPairCodec, IqOrder, Packing, ScalarFormat};
+use crate::fix_converter::{FixCodec, FixFormat};
+use crate::float16_converter::Float16Codec;
+use crate::float32_converter::Float32Codec;
+use crate::float64_converter::Float64Codec;
+use crate::float_converter::FloatCodec;
+use crate::float_format::{FloatFormat, FloatFormatCodec};
+use crate::int_converter::{IntCodec, Radix};
+use crate::overflow::{OverflowHandler, OverflowPolicy};
+use crate::quantized_converter::QuantizedCodec;
+use crate::rounding::{Rounder, RoundingMode};
 use crate::value_converter_factory::ValueType::{
-    Complex, Complex16, Fix16, Fix32, Float, Float16, Float32, ValueTypeNum,
+    BFloat16, Complex, ComplexPair, CustomFloat, Fix, Float, Float16, Float32, Float64, Fp8E4M3,
+    Fp8E5M2, Int, Quantized,
 };
 
-pub trait ValueConverterFactory {
-    fn create(dst: &ValueType, src_bit: u32, dst_bit: u32) -> Box<dyn ValueConverter>;
-
-    fn check(dst: &ValueType) -> bool;
+/// Settings shared by every conversion that drops precision or range,
+/// and the byte order of hex input and output words.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ConvertOptions {
+    pub rounding: RoundingMode,
+    pub overflow: OverflowPolicy,
+    pub src_byte_order: ByteOrder,
+    pub dst_byte_order: ByteOrder,
 }
 
-pub struct FloatConverterFactory;
-
-impl ValueConverterFactory for FloatConverterFactory {
-    fn create(dst: &ValueType, src_bit: u32, dst_bit: u32) -> Box<dyn ValueConverter> {
-        match dst {
-            Float32 => Box::new(FloatToFloat32Converter),
-            Float16 => Box::new(FloatToFloat16Converter),
-            Complex16 => Box::new(FloatToFix32Converter { bit: 0 }),
-            Complex => Box::new(FloatToFix16Converter { bit: 0 }),
-            Fix32 => Box::new(FloatToFix32Converter { bit: dst_bit }),
-            Fix16 => Box::new(FloatToFix16Converter { bit: dst_bit }),
-            _ => Box::new(SelfConverter {
-                value_type: Float as i32,
-            }),
-        }
-    }
+pub struct ConverterFactory;
 
-    fn check(dst: &ValueType) -> bool {
-        [Float32, Float16, Complex16, Complex, Fix32, Fix16].contains(dst)
+impl ConverterFactory {
+    pub fn create(
+        src: &ValueType,
+        dst: &ValueType,
+        options: &ConvertOptions,
+    ) -> Box<dyn ValueConverter> {
+        Box::new(PivotConverter {
+            decoder: Self::ordered_codec(src, options, options.src_byte_order),
+            encoder: Self::ordered_codec(dst, options, options.dst_byte_order),
+        })
     }
-}
 
-pub struct Float32ConverterFactory;
-
-impl ValueConverterFactory for Float32ConverterFactory {
-    fn create(dst: &ValueType, src_bit: u32, dst_bit: u32) -> Box<dyn ValueConverter> {
-        match dst {
-            Float16 => Box::new(Float32ToFloat16Converter),
-            Float => Box::new(Float32ToFloatConverter),
-            Complex => Box::new(Float32ToComplexConverter),
-            Fix32 => Box::new(Float32ToFix32Converter { bit: dst_bit }),
-            Fix16 => Box::new(Float32ToFix16Converter { bit: dst_bit }),
-            _ => Box::new(SelfConverter {
-                value_type: Float32 as i32,
+    fn ordered_codec(
+        value_type: &ValueType,
+        options: &ConvertOptions,
+        order: ByteOrder,
+    ) -> Box<dyn ValueCodec> {
+        let codec = Self::codec(value_type, options);
+        match value_type.word_width() {
+            Some(width) if order != ByteOrder::Big => Box::new(ByteOrderCodec {
+                inner: codec,
+                order,
+                width,
             }),
+            _ => codec,
         }
     }
 
-    fn check(dst: &ValueType) -> bool {
-        [Float16, Float, Complex, Fix32, Fix16].contains(dst)
-    }
-}
-
-pub struct Float16ConverterFactory;
-
-impl ValueConverterFactory for Float16ConverterFactory {
-    fn create(dst: &ValueType, src_bit: u32, dst_bit: u32) -> Box<dyn ValueConverter> {
-        match dst {
-            Float32 => Box::new(Float16ToFloat32Converter),
-            Float => Box::new(Float16ToFloatConverter),
-            Fix32 => Box::new(Float16ToFix32Converter { bit: dst_bit }),
-            Fix16 => Box::new(Float16ToFix16Converter { bit: dst_bit }),
-            _ => Box::new(SelfConverter {
-                value_type: Float16 as i32,
+    fn codec(value_type: &ValueType, options: &ConvertOptions) -> Box<dyn ValueCodec> {
+        let rounder = Rounder::new(options.rounding);
+        match value_type {
+            Float64 => Box::new(Float64Codec { rounder }),
+            Float32 => Box::new(Float32Codec { rounder }),
+            Float16 => Box::new(Float16Codec { rounder }),
+            BFloat16 => Box::new(BFloat16Codec { rounder }),
+            Fp8E4M3 { saturate } => Box::new(FloatFormatCodec {
+                format: FloatFormat::E4M3,
+                saturate: *saturate,
+                rounder,
+            }),
+            Fp8E5M2 { saturate } => Box::new(FloatFormatCodec {
+                format: FloatFormat::E5M2,
+                saturate: *saturate,
+                rounder,
+            }),
+            CustomFloat { format, saturate } => Box::new(FloatFormatCodec {
+                format: *format,
+                saturate: *saturate,
+                rounder,
+            }),
+            Int { format, radix } => Box::new(IntCodec {
+                format: *format,
+                radix: *radix,
+                rounder,
+                overflow: OverflowHandler::new(options.overflow),
+            }),
+            Quantized {
+                storage,
+                radix,
+                scale,
+                zero_point,
+            } => Box::new(QuantizedCodec {
+                storage: IntCodec {
+                    format: *storage,
+                    radix: *radix,
+                    rounder,
+                    overflow: OverflowHandler::new(options.overflow),
+                },
+                scale: *scale,
+                zero_point: *zero_point,
+            }),
+            Float => Box::new(FloatCodec),
+            Complex { style } => Box::new(ComplexCodec { style: *style }),
+            Fix(format) => Box::new(FixCodec {
+                format: *format,
+                rounder,
+                overflow: OverflowHandler::new(options.overflow),
+            }),
+            ComplexPair {
+                component,
+                order,
+                packing,
+            } => Box::new(ComplexPairCodec {
+                component: Self::word_codec(component, options),
+                order: *order,
+                packing: *packing,
             }),
         }
     }
 
-    fn check(dst: &ValueType) -> bool {
-        [Float32, Float, Fix32, Fix16].contains(dst)
-    }
-}
-
-pub struct Fix32ConverterFactory;
-
-impl ValueConverterFactory for Fix32ConverterFactory {
-    fn create(dst: &ValueType, src_bit: u32, dst_bit: u32) -> Box<dyn ValueConverter> {
-        match dst {
-            Float32 => Box::new(Fix32ToFloat32Converter { bit: src_bit }),
-            Float => Box::new(Fix32ToFloatConverter { bit: src_bit }),
-            _ => Box::new(SelfConverter {
-                value_type: Fix32 as i32,
+    fn word_codec(format: &ScalarFormat, options: &ConvertOptions) -> Box<dyn WordCodec> {
+        let rounder = Rounder::new(options.rounding);
+        match format {
+            ScalarFormat::Float { format, saturate } => Box::new(FloatFormatCodec {
+                format: *format,
+                saturate: *saturate,
+                rounder,
+            }),
+            ScalarFormat::Fix(format) => Box::new(FixCodec {
+                format: *format,
+                rounder,
+                overflow: OverflowHandler::new(options.overflow),
             }),
         }
     }
-
-    fn check(dst: &ValueType) -> bool {
-        [Float32, Float].contains(dst)
-    }
 }
 
-pub struct Fix16ConverterFactory;
+#[derive(Clone, Copy, PartialEq)]
+pub enum ValueType {
+    Float64,
+    Float32,
+    Float16,
+    BFloat16,
+    /// OCP FP8 formats; `saturate` clamps overflow to the largest finite
+    /// value instead of producing Inf (E5M2) or NaN (E4M3).
+    Fp8E4M3 { saturate: bool },
+    Fp8E5M2 { saturate: bool },
+    /// A float format defined at runtime, e.g. a 24-bit DSP float or TF32.
+    CustomFloat { format: FloatFormat, saturate: bool },
+    /// An 8, 16, 32 or 64-bit integer written in `radix`; `format` has no
+    /// fraction bits but may hold a narrower sample.
+    Int { format: FixFormat, radix: Radix },
+    /// Integers `q` of the `storage` layout standing for
+    /// `scale * (q - zero_point)`; `scale` is positive.
+    Quantized {
+        storage: FixFormat,
+        radix: Radix,
+        scale: f64,
+        zero_point: i64,
+    },
+    Float,
+    /// Complex text; `style` selects the output notation.
+    Complex { style: ComplexStyle },
+    Fix(FixFormat),
+    /// Complex samples whose components have any hex scalar format, e.g.
+    /// the `complex16`, `complex32` and `fix_complex` presets.
+    ComplexPair {
+        component: ScalarFormat,
+        order: IqOrder,
+        packing: Packing,
+    },
+}
 
-impl ValueConverterFactory for Fix16ConverterFactory {
-    fn create(dst: &ValueType, src_bit: u32, dst_bit: u32) -> Box<dyn ValueConverter> {
-        match dst {
-            Float16 => Box::new(Fix16ToFloat16Converter { bit: src_bit }),
-            Float => Box::new(Fix16ToFloatConverter { bit: src_bit }),
-            _ => Box::new(SelfConverter {
-                value_type: Fix16 as i32,
-            }),
+impl Display for ValueType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Float64 => write!(f, "float64"),
+            Float32 => write!(f, "float32"),
+            Float16 => write!(f, "float16"),
+            BFloat16 => write!(f, "bfloat16"),
+            Fp8E4M3 { .. } => write!(f, "fp8_e4m3"),
+            Fp8E5M2 { .. } => write!(f, "fp8_e5m2"),
+            CustomFloat { .. } => write!(f, "custom"),
+            Int { format, .. } => {
+                write!(f, "{}int{}", if format.signed { "" } else { "u" }, format.width)
+            }
+            Quantized { storage, .. } => {
+                write!(f, "q{}int{}", if storage.signed { "" } else { "u" }, storage.width)
+            }
+            Float => write!(f, "float"),
+            Complex { .. } => write!(f, "complex"),
+            Fix(_) => write!(f, "fix"),
+            ComplexPair { .. } => write!(f, "complex_pair"),
         }
     }
-
-    fn check(dst: &ValueType) -> bool {
-        [Float16, Float].contains(dst)
-    }
 }
 
-pub struct Complex16ConverterFactory;
-
-impl ValueConverterFactory for Complex16ConverterFactory {
-    fn create(dst: &ValueType, src_bit: u32, dst_bit: u32) -> Box<dyn ValueConverter> {
-        match dst {
-            Complex => Box::new(Complex16ToComplexConverter),
-            Float32 => Box::new(ComplexToFloat32Converter),
-            _ => Box::new(SelfConverter { value_type: Complex16 as i32 })
+impl ValueType {
+    /// Parses a type name as shown by `Display`. `fix16` and `fix32` select
+    /// signed integer words of that width; their fraction bits default to zero.
+    /// `tf32` and custom float specs such as `e7m16` or `e4m3,nan-only` (see
+    /// `FloatFormat`'s `Display`) select custom float formats. Integer types
+    /// such as `int16` or `uint8` default to hex, as do quantized types such
+    /// as `qint8` or `quint8`, which start with a unit scale and zero point 0.
+    /// `complex16`, `complex32` and `fix_complex` are complex pairs of
+    /// float16, float32 and Q0.15 components packed in one word.
+    pub fn get_value_type(string: &str) -> Option<ValueType> {
+        match string {
+            "float64" => Some(Float64),
+            "float32" => Some(Float32),
+            "float16" => Some(Float16),
+            "bfloat16" => Some(BFloat16),
+            "fp8_e4m3" => Some(Fp8E4M3 { saturate: false }),
+            "fp8_e5m2" => Some(Fp8E5M2 { saturate: false }),
+            "custom" => Some(custom_float(FloatFormat::default())),
+            "tf32" => Some(custom_float(FloatFormat::TF32)),
+            "int8" => Some(int(8, true)),
+            "int16" => Some(int(16, true)),
+            "int32" => Some(int(32, true)),
+            "int64" => Some(int(64, true)),
+            "uint8" => Some(int(8, false)),
+            "uint16" => Some(int(16, false)),
+            "uint32" => Some(int(32, false)),
+            "uint64" => Some(int(64, false)),
+            "qint8" => Some(quantized(8, true)),
+            "qint16" => Some(quantized(16, true)),
+            "qint32" => Some(quantized(32, true)),
+            "quint8" => Some(quantized(8, false)),
+            "quint16" => Some(quantized(16, false)),
+            "quint32" => Some(quantized(32, false)),
+            "float" => Some(Float),
+            "fix" => Some(Fix(FixFormat::default())),
+            "fix32" => Some(Fix(FixFormat::new(32, true, 0))),
+            "fix16" => Some(Fix(FixFormat::new(16, true, 0))),
+            "fix_complex" => Some(complex_pair(ScalarFormat::Fix(FixFormat::default()))),
+            "complex16" => Some(complex_pair(ScalarFormat::default())),
+            "complex32" => Some(complex_pair(ScalarFormat::Float {
+                format: FloatFormat::BINARY32,
+                saturate: false,
+            })),
+            "complex_pair" => Some(complex_pair(ScalarFormat::default())),
+            "complex" => Some(Complex {
+                style: ComplexStyle::default(),
+            }),
+            spec => spec.parse().ok().map(custom_float),
         }
     }
 
-    fn check(dst: &ValueType) -> bool {
-        [Complex].contains(dst)
+    /// Whether values are written as hex words, to which a byte order applies.
+    pub fn is_hex(&self) -> bool {
+        self.word_width().is_some()
     }
-}
 
-pub struct ComplexConverterFactory;
-
-impl ValueConverterFactory for ComplexConverterFactory {
-    fn create(dst: &ValueType, src_bit: u32, dst_bit: u32) -> Box<dyn ValueConverter> {
-        match dst {
-            Complex16 => Box::new(ComplexToComplex16Converter),
-            Float32 => Box::new(ComplexToFloat32Converter),
-            _ => Box::new(SelfConverter { value_type: Complex as i32 })
+    /// Bits in each hex word of the type, or `None` when it is not written
+    /// in hex.
+    pub fn word_width(&self) -> Option<u32> {
+        match self {
+            Float | Complex { .. } => None,
+            Int { radix, .. } | Quantized { radix, .. } if *radix != Radix::Hex => None,
+            Quantized { storage, .. } => Some(storage.width),
+            ComplexPair {
+                component, packing, ..
+            } => Some(match packing.for_component_width(component.width()) {
+                Packing::OneWord => 2 * component.width(),
+                Packing::TwoWords => component.width(),
+            }),
+            _ => self.scalar_format().map(|format| format.width()),
         }
     }
 
-    fn check(dst: &ValueType) -> bool {
-        [Complex16].contains(dst)
-    }
-}
-
-pub struct ConverterFactory;
-
-impl ConverterFactory {
-    pub(crate) fn create(
-        src: &ValueType,
-        dst: &ValueType,
-        src_bit: u32,
-        dst_bit: u32,
-    ) -> Box<dyn ValueConverter> {
-        match src {
-            Float32 => Float32ConverterFactory::create(dst, src_bit, dst_bit),
-            Float16 => Float16ConverterFactory::create(dst, src_bit, dst_bit),
-            Float => FloatConverterFactory::create(dst, src_bit, dst_bit),
-            Complex16 => Complex16ConverterFactory::create(dst, src_bit, dst_bit),
-            Complex => ComplexConverterFactory::create(dst, src_bit, dst_bit),
-            Fix32 => Fix32ConverterFactory::create(dst, src_bit, dst_bit),
-            Fix16 => Fix16ConverterFactory::create(dst, src_bit, dst_bit),
-            ValueTypeNum => Box::new(SelfConverter { value_type: 0 }),
+    /// The bit layout of a hex scalar type, usable as a complex component.
+    pub fn scalar_format(&self) -> Option<ScalarFormat> {
+        let float = |format, saturate| ScalarFormat::Float { format, saturate };
+        match *self {
+            Float64 => Some(float(FloatFormat::BINARY64, false)),
+            Float32 => Some(float(FloatFormat::BINARY32, false)),
+            Float16 => Some(float(FloatFormat::BINARY16, false)),
+            BFloat16 => Some(float(FloatFormat::BFLOAT16, false)),
+            Fp8E4M3 { saturate } => Some(float(FloatFormat::E4M3, saturate)),
+            Fp8E5M2 { saturate } => Some(float(FloatFormat::E5M2, saturate)),
+            CustomFloat { format, saturate } => Some(float(format, saturate)),
+            Int { format, .. } => Some(ScalarFormat::Fix(format)),
+            Fix(format) => Some(ScalarFormat::Fix(format)),
+            _ => None,
         }
     }
+}
 
-    pub fn check(src: &ValueType, dst: &ValueType) -> bool {
-        match src {
-            Float32 => Float32ConverterFactory::check(dst),
-            Float16 => Float16ConverterFactory::check(dst),
-            Float => FloatConverterFactory::check(dst),
-            Complex16 => Complex16ConverterFactory::check(dst),
-            Complex => ComplexConverterFactory::check(dst),
-            Fix32 => Fix32ConverterFactory::check(dst),
-            Fix16 => Fix16ConverterFactory::check(dst),
-            ValueTypeNum => false,
-        }
+fn int(width: u32, signed: bool) -> ValueType {
+    Int {
+        format: FixFormat::new(width, signed, 0),
+        radix: Radix::Hex,
     }
 }
 
-#[derive(Clone, Copy, PartialEq)]
-pub enum ValueType {
-    Float32 = 0,
-    Float16,
-    Float,
-    Complex16,
-    Complex,
-    Fix32,
-    Fix16,
-    ValueTypeNum,
+fn quantized(width: u32, signed: bool) -> ValueType {
+    Quantized {
+        storage: FixFormat::new(width, signed, 0),
+        radix: Radix::Hex,
+        scale: 1.0,
+        zero_point: 0,
+    }
 }
 
-impl Display for ValueType {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Float32 => write!(f, "{}", "float32"),
-            Float16 => write!(f, "{}", "float16"),
-            Float => write!(f, "{}", "float"),
-            Complex16 => write!(f, "{}", "complex16"),
-            Complex => write!(f, "{}", "complex"),
-            Fix32 => write!(f, "{}", "fix32"),
-            Fix16 => write!(f, "{}", "fix16"),
-            ValueTypeNum => write!(f, "{}", "value_type_num"),
-        }
+/// Both components in one word, real part first.
+fn complex_pair(component: ScalarFormat) -> ValueType {
+    ComplexPair {
+        component,
+        order: IqOrder::default(),
+        packing: Packing::default(),
     }
 }
 
-impl ValueType {
-    pub fn get_value_type(string: &str) -> ValueType {
-        match string {
-            "float32" => Float32,
-            "float16" => Float16,
-            "float" => Float,
-            "fix32" => Fix32,
-            "fix16" => Fix16,
-            "complex16" => Complex16,
-            "complex" => Complex,
-            &_ => {
-                println!("Invalid value type: {}", string);
-                ValueTypeNum
-            }
-        }
+fn custom_float(format: FloatFormat) -> ValueType {
+    CustomFloat {
+        format,
+        saturate: false,
     }
 }